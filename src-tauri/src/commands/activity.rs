@@ -0,0 +1,195 @@
+use super::thermodynamics::Component;
+use serde::{Deserialize, Serialize};
+
+/// Gas constant in cal/(mol K)
+const R: f64 = 1.98721;
+/// UNIQUAC lattice coordination number
+const Z: f64 = 10.0;
+
+/// Temperature dependent parameter evaluated as a + b / T, with T in K
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Interaction {
+    pub a: f64,
+    #[serde(default)]
+    pub b: f64,
+}
+
+impl Interaction {
+    pub const fn new(a: f64, b: f64) -> Self {
+        Self { a, b }
+    }
+
+    pub fn at(&self, temperature_k: f64) -> f64 {
+        self.a + self.b / temperature_k
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ActivityModelKind {
+    #[default]
+    VanLaar,
+    Margules2,
+    Margules3,
+    Wilson,
+    Nrtl,
+    Uniquac,
+}
+
+impl ActivityModelKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActivityModelKind::VanLaar => "Van Laar",
+            ActivityModelKind::Margules2 => "Margules (2 parameters)",
+            ActivityModelKind::Margules3 => "Margules (3 parameters)",
+            ActivityModelKind::Wilson => "Wilson",
+            ActivityModelKind::Nrtl => "NRTL",
+            ActivityModelKind::Uniquac => "UNIQUAC",
+        }
+    }
+}
+
+/// Activity coefficient model of a binary mixture, component 1 being the light key.
+///
+/// Wilson energies are in cal/mol and use the molar volumes of the components,
+/// NRTL parameters are the dimensionless tau_ij and UNIQUAC parameters are ln(tau_ij).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(
+    tag = "model",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ActivityModel {
+    VanLaar {
+        a_12: f64,
+        a_21: f64,
+    },
+    Margules2 {
+        a: Interaction,
+    },
+    Margules3 {
+        a_12: Interaction,
+        a_21: Interaction,
+    },
+    Wilson {
+        lambda_12: f64,
+        lambda_21: f64,
+    },
+    Nrtl {
+        alpha: f64,
+        tau_12: Interaction,
+        tau_21: Interaction,
+    },
+    Uniquac {
+        tau_12: Interaction,
+        tau_21: Interaction,
+    },
+}
+
+impl ActivityModel {
+    /// Activity coefficients (gamma_1, gamma_2) at liquid composition x_1 and temperature in °C
+    pub fn gammas(&self, light: &Component, heavy: &Component, x_1: f64, temp: f64) -> (f64, f64) {
+        let x_2 = 1.0 - x_1;
+        let t = temp + 273.15;
+
+        let (ln_gamma_1, ln_gamma_2) = match *self {
+            ActivityModel::VanLaar { a_12, a_21 } => van_laar(a_12, a_21, x_1, x_2),
+            ActivityModel::Margules2 { a } => {
+                let a = a.at(t);
+                (a * x_2 * x_2, a * x_1 * x_1)
+            }
+            ActivityModel::Margules3 { a_12, a_21 } => {
+                let (a_12, a_21) = (a_12.at(t), a_21.at(t));
+                (
+                    x_2 * x_2 * (a_12 + 2.0 * (a_21 - a_12) * x_1),
+                    x_1 * x_1 * (a_21 + 2.0 * (a_12 - a_21) * x_2),
+                )
+            }
+            ActivityModel::Wilson {
+                lambda_12,
+                lambda_21,
+            } => {
+                let volume_ratio = heavy.molar_volume / light.molar_volume;
+                let l_12 = volume_ratio * (-lambda_12 / (R * t)).exp();
+                let l_21 = (-lambda_21 / (R * t)).exp() / volume_ratio;
+                wilson(l_12, l_21, x_1, x_2)
+            }
+            ActivityModel::Nrtl {
+                alpha,
+                tau_12,
+                tau_21,
+            } => nrtl(alpha, tau_12.at(t), tau_21.at(t), x_1, x_2),
+            ActivityModel::Uniquac { tau_12, tau_21 } => uniquac(
+                light,
+                heavy,
+                tau_12.at(t).exp(),
+                tau_21.at(t).exp(),
+                x_1,
+                x_2,
+            ),
+        };
+
+        (ln_gamma_1.exp(), ln_gamma_2.exp())
+    }
+}
+
+fn van_laar(a_12: f64, a_21: f64, x_1: f64, x_2: f64) -> (f64, f64) {
+    let denominator = a_12 * x_1 + a_21 * x_2;
+    (
+        a_12 * (a_21 * x_2 / denominator).powi(2),
+        a_21 * (a_12 * x_1 / denominator).powi(2),
+    )
+}
+
+fn wilson(l_12: f64, l_21: f64, x_1: f64, x_2: f64) -> (f64, f64) {
+    let term = l_12 / (x_1 + l_12 * x_2) - l_21 / (x_2 + l_21 * x_1);
+    (
+        -(x_1 + l_12 * x_2).ln() + x_2 * term,
+        -(x_2 + l_21 * x_1).ln() - x_1 * term,
+    )
+}
+
+fn nrtl(alpha: f64, tau_12: f64, tau_21: f64, x_1: f64, x_2: f64) -> (f64, f64) {
+    let g_12 = (-alpha * tau_12).exp();
+    let g_21 = (-alpha * tau_21).exp();
+    let d_1 = x_1 + x_2 * g_21;
+    let d_2 = x_2 + x_1 * g_12;
+    (
+        x_2 * x_2 * (tau_21 * (g_21 / d_1).powi(2) + tau_12 * g_12 / d_2.powi(2)),
+        x_1 * x_1 * (tau_12 * (g_12 / d_2).powi(2) + tau_21 * g_21 / d_1.powi(2)),
+    )
+}
+
+fn uniquac(
+    light: &Component,
+    heavy: &Component,
+    tau_12: f64,
+    tau_21: f64,
+    x_1: f64,
+    x_2: f64,
+) -> (f64, f64) {
+    let (r_1, q_1) = (light.r, light.q);
+    let (r_2, q_2) = (heavy.r, heavy.q);
+    let l_1 = Z / 2.0 * (r_1 - q_1) - (r_1 - 1.0);
+    let l_2 = Z / 2.0 * (r_2 - q_2) - (r_2 - 1.0);
+
+    let sum_r = r_1 * x_1 + r_2 * x_2;
+    let sum_q = q_1 * x_1 + q_2 * x_2;
+    let (phi_1, phi_2) = (r_1 * x_1 / sum_r, r_2 * x_2 / sum_r);
+    let (theta_1, theta_2) = (q_1 * x_1 / sum_q, q_2 * x_2 / sum_q);
+
+    // phi_i / x_i and theta_i / phi_i written so they stay finite at infinite dilution
+    let combinatorial_1 = (r_1 / sum_r).ln()
+        + Z / 2.0 * q_1 * (q_1 / r_1 * sum_r / sum_q).ln()
+        + phi_2 * (l_1 - r_1 / r_2 * l_2);
+    let combinatorial_2 = (r_2 / sum_r).ln()
+        + Z / 2.0 * q_2 * (q_2 / r_2 * sum_r / sum_q).ln()
+        + phi_1 * (l_2 - r_2 / r_1 * l_1);
+
+    let d_1 = theta_1 + theta_2 * tau_21;
+    let d_2 = theta_2 + theta_1 * tau_12;
+    let residual_1 = -q_1 * d_1.ln() + theta_2 * q_1 * (tau_21 / d_1 - tau_12 / d_2);
+    let residual_2 = -q_2 * d_2.ln() + theta_1 * q_2 * (tau_12 / d_2 - tau_21 / d_1);
+
+    (combinatorial_1 + residual_1, combinatorial_2 + residual_2)
+}
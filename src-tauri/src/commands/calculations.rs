@@ -1,124 +1,366 @@
+use super::error::AppError;
 use super::modbus_serial::{read_holding_registers, CurrentConnection};
+use super::registers::RegisterFormat;
 use super::settings::SettingsState;
-use std::f64::consts::E;
+use super::solver::{solve_bracketed, solve_segmented, SolverError, SolverResult};
+use super::thermodynamics::BinaryMixture;
+use super::validation::validate_pressure;
+use serde::Serialize;
 use tauri::State;
 use tokio::sync::Mutex;
 
-pub fn calculate_composition(x_0: f64, temp: f64, tol: f64, max_iter: u64) -> Result<f64, String> {
-    let mut x = x_0;
-    const H: f64 = 1e-5;
+pub struct SensorReadings {
+    /// Temperature of every plate, `None` for plates without a sensor
+    pub temperatures: Vec<Option<f64>>,
+    pub pressure: f64,
+}
 
-    for _ in 0..max_iter {
-        let fx = calculate_residual(x, temp);
-        let fx_prime =
-            (calculate_residual(x + H, temp) - calculate_residual(x - H, temp)) / (2.0 * H);
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct EquilibriumPoint {
+    pub x: f64,
+    pub y: f64,
+    pub temperature: f64,
+}
 
-        if fx_prime.abs() < 1e-12 {
-            return Err("Error. Division by zero.".to_string());
-        }
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EquilibriumCurve {
+    pub mixture: String,
+    pub pressure: f64,
+    pub points: Vec<EquilibriumPoint>,
+}
 
-        let x_next = x - fx / fx_prime;
+/// Sub-intervals of [0, 1] searched for a root when the residual has the same
+/// sign at both pure components
+const COMPOSITION_SEGMENTS: usize = 50;
 
-        if (x_next - x).abs() < tol {
-            return Ok(x_next);
-        }
+/// Liquid mole fraction of the light component at its bubble point for `temp`.
+/// The search is bounded to [0, 1] and starts from `x_0`. Between the azeotrope
+/// and the lighter component's boiling point there are two roots, the one
+/// closest to `x_0` is returned.
+pub fn calculate_composition(
+    mixture: &BinaryMixture,
+    pressure: f64,
+    x_0: f64,
+    temp: f64,
+    tol: f64,
+    max_iter: u64,
+) -> Result<SolverResult, SolverError> {
+    solve_segmented(
+        |x| calculate_residual(mixture, pressure, x, temp),
+        0.0,
+        1.0,
+        x_0,
+        tol,
+        max_iter,
+        COMPOSITION_SEGMENTS,
+    )
+}
+
+/// Liquid and vapor compositions of every plate
+pub struct PlateCompositions {
+    pub liquid: Vec<f64>,
+    pub vapor: Vec<f64>,
+    /// Plates the solver failed on, given the composition of the plate below
+    pub failures: usize,
+}
+
+/// Compositions of every plate from its temperature. Each plate is warm started
+/// from `previous`, the compositions of the last cycle, or from the plate below.
+/// Empty when the temperatures are outside the boiling range at `pressure`.
+pub fn plate_compositions(
+    mixture: &BinaryMixture,
+    pressure: f64,
+    temperatures: &[f64],
+    previous: Option<&[f64]>,
+) -> PlateCompositions {
+    let tol = 1e-6;
+    let max_iter = 1000;
+    let mut plates = PlateCompositions {
+        liquid: Vec::with_capacity(temperatures.len()),
+        vapor: Vec::with_capacity(temperatures.len()),
+        failures: 0,
+    };
+
+    let (min_t, max_t) = mixture.boiling_range(pressure);
+    let valid_range = temperatures.first().unwrap_or(&0.0) > &min_t
+        && temperatures.last().unwrap_or(&0.0) < &max_t;
+    if !valid_range {
+        return plates;
+    }
 
-        x = x_next;
+    for (i, &temp) in temperatures.iter().enumerate() {
+        let x_0 = previous
+            .and_then(|compositions| compositions.get(i).copied())
+            .or_else(|| plates.liquid.last().copied())
+            .unwrap_or(0.5);
+
+        let composition = match calculate_composition(mixture, pressure, x_0, temp, tol, max_iter) {
+            Ok(result) => result.value,
+            Err(error) => {
+                let error = AppError::Solver {
+                    plate: Some(i),
+                    error,
+                };
+                eprintln!("{}", error);
+                plates.failures += 1;
+                plates.liquid.last().copied().unwrap_or(0.0)
+            }
+        };
+
+        plates.liquid.push(composition);
+        plates.vapor.push(calculate_vapor_composition(
+            mixture,
+            pressure,
+            composition,
+            temp,
+        ));
     }
+    plates
+}
+
+/// Bubble temperature in °C of a liquid with light component mole fraction `x_1`
+pub fn calculate_bubble_temperature(
+    mixture: &BinaryMixture,
+    pressure: f64,
+    x_1: f64,
+    tol: f64,
+    max_iter: u64,
+) -> Result<SolverResult, SolverError> {
+    let (t_light, t_heavy) = mixture.boiling_range(pressure);
+    // minimum boiling azeotropes bubble below the lighter pure component
+    let low = t_light.min(t_heavy) - 50.0;
+    let high = t_light.max(t_heavy) + 10.0;
+    let t_0 = t_heavy + (t_light - t_heavy) * x_1;
 
-    Err("No value founded".to_string())
+    solve_bracketed(
+        |temp| calculate_residual(mixture, pressure, x_1, temp),
+        low,
+        high,
+        t_0,
+        tol,
+        max_iter,
+    )
 }
 
-fn calculate_residual(x_1: f64, temp: f64) -> f64 {
-    // Parameters Ethanol (1) - Water (2)
-    const A1: f64 = 8.12875;
-    const B1: f64 = 1660.8713;
-    const C1: f64 = 238.131;
-    const AVAN1: f64 = 1.6798;
+/// Vapor mole fraction of the light component in equilibrium with `x_1` at `temp`
+pub fn calculate_vapor_composition(
+    mixture: &BinaryMixture,
+    pressure: f64,
+    x_1: f64,
+    temp: f64,
+) -> f64 {
+    let (y1, y2) = calculate_vapor(mixture, pressure, x_1, temp);
+    y1 / (y1 + y2)
+}
 
-    const A2: f64 = 8.05573;
-    const B2: f64 = 1723.6425;
-    const C2: f64 = 233.08;
-    const AVAN2: f64 = 0.9227;
+fn calculate_residual(mixture: &BinaryMixture, pressure: f64, x_1: f64, temp: f64) -> f64 {
+    let (y1, y2) = calculate_vapor(mixture, pressure, x_1, temp);
+    y1 + y2 - 1.0
+}
 
-    const P: f64 = 585.0;
+fn calculate_vapor(mixture: &BinaryMixture, pressure: f64, x_1: f64, temp: f64) -> (f64, f64) {
     let x_2 = 1.0 - x_1;
 
-    let (gamma_1, gamma_2) = calculate_gammas(AVAN1, AVAN2, x_1, x_2);
+    let (gamma_1, gamma_2) = mixture.gammas(x_1, temp);
 
-    let ps_1 = calculate_ps(temp, A1, B1, C1);
-    let ps_2 = calculate_ps(temp, A2, B2, C2);
+    let ps_1 = mixture.light.antoine.vapor_pressure(temp);
+    let ps_2 = mixture.heavy.antoine.vapor_pressure(temp);
 
-    let k_1 = calculate_ks(gamma_1, ps_1, P);
-    let k_2 = calculate_ks(gamma_2, ps_2, P);
+    let k_1 = calculate_ks(gamma_1, ps_1, pressure);
+    let k_2 = calculate_ks(gamma_2, ps_2, pressure);
 
     let y1 = calculate_y(k_1, x_1);
     let y2 = calculate_y(k_2, x_2);
 
-    return y1 + y2 - 1.0;
+    (y1, y2)
 }
 
-pub async fn read_temperatures(
+#[tauri::command]
+pub async fn equilibrium_curve(
+    settings_state: State<'_, Mutex<SettingsState>>,
+    pressure: Option<f64>,
+    points: Option<usize>,
+) -> Result<EquilibriumCurve, AppError> {
+    let settings = {
+        let current_settings = settings_state.lock().await;
+        current_settings.settings.clone()
+    }
+    .ok_or(AppError::no_data("No settings found"))?;
+
+    validate_pressure(pressure)?;
+    let mixture = settings.mixture_model();
+    let pressure = pressure.unwrap_or(settings.pressure.value);
+    let points = points.unwrap_or(51).max(2);
+
+    let mut curve = Vec::with_capacity(points);
+    for i in 0..points {
+        let x = i as f64 / (points - 1) as f64;
+        let temperature = calculate_bubble_temperature(&mixture, pressure, x, 1e-6, 1000)?.value;
+        curve.push(EquilibriumPoint {
+            x,
+            y: calculate_vapor_composition(&mixture, pressure, x, temperature),
+            temperature,
+        });
+    }
+
+    Ok(EquilibriumCurve {
+        mixture: settings.mixture.name(),
+        pressure,
+        points: curve,
+    })
+}
+
+pub async fn read_sensors(
     settings_state: &State<'_, Mutex<SettingsState>>,
     connection_state: State<'_, Mutex<CurrentConnection>>,
-) -> Result<Vec<f64>, String> {
+) -> Result<SensorReadings, AppError> {
     let settings = {
         let current_settings = settings_state.lock().await;
         current_settings.settings.clone()
     };
     let Some(settings) = settings else {
-        return Err("Error to get settings".into());
+        return Err(AppError::no_data("No settings found"));
     };
 
-    let temperatures = read_holding_registers(
-        connection_state,
-        settings.temperature_address.bottom,
-        settings.count,
-        settings.timeout,
-        settings.unit_id,
-    )
-    .await
-    .map_err(|e| format!("Error in read temperatures {:?}", e))?;
+    let number_plates = settings.number_plates.max(2);
+    let mut temperatures: Vec<Option<f64>> = vec![None; number_plates];
+    for sensor in settings.temperature_sensors() {
+        if sensor.plate >= number_plates {
+            eprintln!(
+                "Sensor at address {} assigned to plate {} outside the column",
+                sensor.address, sensor.plate
+            );
+            continue;
+        }
 
-    let temperature_bottom = temperatures[0].value;
-    let temperature_top = temperatures[1].value;
+        let raw = read_channel(
+            connection_state.clone(),
+            sensor.address,
+            &sensor.format,
+            settings.timeout,
+            sensor.unit_id.unwrap_or(settings.unit_id),
+        )
+        .await?;
 
-    let temperature_bottom_format = temperature_bottom as f64 / 100.0;
-    let temperature_top_format = temperature_top as f64 / 100.0;
+        temperatures[sensor.plate] =
+            Some(sensor.unit.to_celsius(raw * sensor.scale + sensor.offset));
+    }
 
-    return Ok(vec![temperature_bottom_format, temperature_top_format]);
-}
+    let pressure = match settings.pressure.address {
+        Some(address) => {
+            let raw = read_channel(
+                connection_state,
+                address,
+                &settings.pressure.format,
+                settings.timeout,
+                settings.unit_id,
+            )
+            .await?;
+            settings
+                .pressure
+                .unit
+                .to_mmhg(raw * settings.pressure.scale + settings.pressure.offset)
+        }
+        None => settings.pressure.value,
+    };
 
-pub fn interpolate_temperatures(num_plates: usize, t1: f64, tn: f64) -> Vec<f64> {
-    if num_plates <= 2 {
-        return vec![t1, tn];
-    }
-    let mut interpolated_temps = Vec::with_capacity(num_plates);
-    for i in 0..num_plates {
-        let temp: f64 = t1 + (i as f64) / (num_plates as f64 - 1.0) * (tn - t1);
-        interpolated_temps.push(temp);
-    }
-    interpolated_temps
+    Ok(SensorReadings {
+        temperatures,
+        pressure,
+    })
 }
 
-fn calculate_ps(temperature: f64, a: f64, b: f64, c: f64) -> f64 {
-    let log10_p: f64 = a - b / (c + temperature);
-
-    let p = 10.0f64.powf(log10_p);
-    return p;
+/// Reads the registers of a channel and decodes them as its raw value
+async fn read_channel(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    address: u16,
+    format: &RegisterFormat,
+    timeout: u64,
+    unit_id: u8,
+) -> Result<f64, AppError> {
+    let registers = read_holding_registers(
+        connection_state,
+        address,
+        format.data_type.register_count(),
+        timeout,
+        unit_id,
+    )
+    .await?;
+    let values: Vec<u16> = registers.iter().map(|r| r.value).collect();
+    format.decode(&values).ok_or(AppError::Modbus {
+        context: format!("decoding registers at {}", address),
+        message: "Not enough registers read".into(),
+    })
 }
 
-fn calculate_gammas(a_12: f64, a_21: f64, x_1: f64, x_2: f64) -> (f64, f64) {
-    let denominator = a_12 * x_1 + a_21 * x_2;
-    let gamma1 = E.powf(a_12 * (a_21 * x_2 / denominator).powf(2.0));
-    let gamma2 = E.powf(a_21 * (a_12 * x_1 / denominator).powf(2.0));
-    (gamma1, gamma2)
+/// Fills the plates without a sensor by linear interpolation between the nearest
+/// measured plates, holding the end values outside the measured span.
+pub fn interpolate_temperatures(measured: &[Option<f64>]) -> Option<Vec<f64>> {
+    let known: Vec<(usize, f64)> = measured
+        .iter()
+        .enumerate()
+        .filter_map(|(i, temp)| temp.map(|temp| (i, temp)))
+        .collect();
+    if known.is_empty() {
+        return None;
+    }
+
+    let interpolated_temps = (0..measured.len())
+        .map(|i| {
+            if let Some(temp) = measured[i] {
+                return temp;
+            }
+            let below = known.iter().rev().find(|(j, _)| *j < i);
+            let above = known.iter().find(|(j, _)| *j > i);
+            match (below, above) {
+                (Some(&(i0, t0)), Some(&(i1, t1))) => {
+                    t0 + (i - i0) as f64 / (i1 - i0) as f64 * (t1 - t0)
+                }
+                (Some(&(_, temp)), None) | (None, Some(&(_, temp))) => temp,
+                (None, None) => unreachable!("at least one plate is measured"),
+            }
+        })
+        .collect();
+    Some(interpolated_temps)
 }
 
 fn calculate_ks(gamma: f64, ps: f64, p: f64) -> f64 {
-    return gamma * ps / p;
+    gamma * ps / p
 }
 
 fn calculate_y(k: f64, x: f64) -> f64 {
-    return k * x;
+    k * x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::activity::ActivityModelKind;
+    use crate::commands::thermodynamics::Mixture;
+
+    #[test]
+    fn composition_between_azeotrope_and_light_boiling_point() {
+        let mixture = Mixture::EthanolWater.model(ActivityModelKind::VanLaar);
+        let pressure = 760.0;
+        let (t_light, _) = mixture.boiling_range(pressure);
+        // the azeotrope bubbles about 0.1 °C below pure ethanol
+        let temp = t_light - 0.05;
+
+        let below = calculate_composition(&mixture, pressure, 0.5, temp, 1e-9, 1000).unwrap();
+        let above = calculate_composition(&mixture, pressure, 1.0, temp, 1e-9, 1000).unwrap();
+        for result in [below, above] {
+            assert!(result.residual.abs() < 1e-9, "{:?}", result);
+            let bubble =
+                calculate_bubble_temperature(&mixture, pressure, result.value, 1e-9, 1000).unwrap();
+            assert!((bubble.value - temp).abs() < 1e-6, "{:?}", bubble);
+        }
+        // one root on each side of the azeotrope
+        assert!(
+            below.value < 0.9 && above.value > 0.9,
+            "{:?} {:?}",
+            below,
+            above
+        );
+    }
 }
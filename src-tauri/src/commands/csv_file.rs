@@ -0,0 +1,141 @@
+use super::data_manager::ColumnEntry;
+use super::error::AppError;
+use calamine::Data;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A row of cells with its line or spreadsheet row number
+pub type NumberedRow = (usize, Vec<Data>);
+
+/// Separators of CSV files. Locales writing decimals with a comma use `;` as
+/// delimiter and `,` as decimal separator.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub decimal_separator: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl CsvOptions {
+    fn delimiter_byte(&self) -> Result<u8, AppError> {
+        if !self.delimiter.is_ascii() || self.delimiter == '"' {
+            return Err(AppError::settings(
+                "delimiter",
+                format!("Unsupported delimiter {:?}", self.delimiter),
+            ));
+        }
+        if !matches!(self.decimal_separator, '.' | ',') {
+            return Err(AppError::settings(
+                "decimalSeparator",
+                format!(
+                    "Decimal separator must be . or , not {:?}",
+                    self.decimal_separator
+                ),
+            ));
+        }
+        if self.delimiter == self.decimal_separator {
+            return Err(AppError::settings(
+                "delimiter",
+                "Delimiter and decimal separator must differ",
+            ));
+        }
+        Ok(self.delimiter as u8)
+    }
+
+    fn format(&self, value: f64) -> String {
+        let text = value.to_string();
+        if self.decimal_separator == '.' {
+            return text;
+        }
+        text.replace('.', &self.decimal_separator.to_string())
+    }
+
+    /// Reads a field as a number when possible so rows go through the same
+    /// checks as spreadsheet cells
+    fn parse(&self, field: &str) -> Data {
+        let field = field.trim();
+        if field.is_empty() {
+            return Data::Empty;
+        }
+        let number = field.replace(self.decimal_separator, ".");
+        match number.parse::<f64>() {
+            Ok(value) => Data::Float(value),
+            Err(_) => Data::String(field.to_string()),
+        }
+    }
+}
+
+pub fn is_csv(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// Reads every record with its line number. Invalid UTF-8 is kept lossily so
+/// the row is rejected by the importer instead of failing the whole file.
+pub fn read_rows(path: &str, options: &CsvOptions) -> Result<Vec<NumberedRow>, AppError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(options.delimiter_byte()?)
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| AppError::file(path, e))?;
+
+    let mut rows = Vec::new();
+    for record in reader.byte_records() {
+        let record = record.map_err(|e| AppError::file(path, e))?;
+        let line = record
+            .position()
+            .map(|position| position.line() as usize)
+            .unwrap_or(rows.len() + 1);
+        let row = record
+            .iter()
+            .map(|field| options.parse(&String::from_utf8_lossy(field)))
+            .collect();
+        rows.push((line, row));
+    }
+    Ok(rows)
+}
+
+pub fn write_rows(
+    path: &str,
+    options: &CsvOptions,
+    header: &[String],
+    history: &[Arc<ColumnEntry>],
+) -> Result<(), AppError> {
+    let mut writer = WriterBuilder::new()
+        .delimiter(options.delimiter_byte()?)
+        .from_path(path)
+        .map_err(|e| AppError::file(path, e))?;
+
+    writer
+        .write_record(header)
+        .map_err(|e| AppError::file(path, e))?;
+    for entry in history {
+        let mut record = vec![entry.timestamp.to_string(), options.format(entry.pressure)];
+        record.extend(
+            entry
+                .temperatures
+                .iter()
+                .chain(&entry.compositions)
+                .map(|&value| options.format(value)),
+        );
+        // entries out of the boiling range have no compositions
+        record.resize(header.len(), String::new());
+        writer
+            .write_record(&record)
+            .map_err(|e| AppError::file(path, e))?;
+    }
+    writer.flush().map_err(|e| AppError::file(path, e))
+}
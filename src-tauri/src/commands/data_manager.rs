@@ -1,112 +1,122 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::State;
 use tokio::sync::Mutex;
 
-use crate::commands::calculations::{
-    calculate_composition, interpolate_temperatures, read_temperatures,
-};
+use crate::commands::calculations::{interpolate_temperatures, plate_compositions, read_sensors};
 
+use super::error::AppError;
 use super::modbus_serial::CurrentConnection;
+use super::playback::{played_entry, Playback};
 use super::settings::SettingsState;
+use super::thermodynamics::BinaryMixture;
 
 #[derive(Clone)]
 pub enum DataSource {
     Live,
-    Playback {
-        index: u64,
-        data: Vec<Arc<ColumnEntry>>,
-    },
+    Playback(Playback),
 }
 
-#[derive(Default, Clone, Serialize, Debug)]
+#[derive(Default, Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnEntry {
+    /// Milliseconds since the epoch
     pub timestamp: u64,
+    pub pressure: f64,
     pub temperatures: Vec<f64>,
     pub compositions: Vec<f64>,
+    pub vapor_compositions: Vec<f64>,
     pub percentage_complete: f64,
 }
 
-#[derive(Default, Clone, Serialize)]
-pub struct MeasurementHistory {
-    pub history: Vec<Arc<ColumnEntry>>,
+impl ColumnEntry {
+    /// Column layout shared by every export format: timestamp, pressure, the
+    /// temperature of every plate and then the composition of every plate
+    pub fn headers(plates: usize) -> Vec<String> {
+        let mut header = vec!["Timestamp [ms]".to_string(), "Pressure [mmHg]".to_string()];
+        header.extend((1..=plates).map(|i| format!("Temperature {} [°C]", i)));
+        header.extend((1..=plates).map(|i| format!("Composition {} [mol frac]", i)));
+        header
+    }
+
+    /// Copy with the compositions computed again from the temperatures with
+    /// `mixture`, at `pressure` or else the recorded pressure. Also returns the
+    /// plates the solver failed on.
+    pub fn recompute(
+        &self,
+        mixture: &BinaryMixture,
+        pressure: Option<f64>,
+        previous: Option<&[f64]>,
+    ) -> (ColumnEntry, usize) {
+        let pressure = pressure.unwrap_or(self.pressure);
+        let plates = plate_compositions(mixture, pressure, &self.temperatures, previous);
+        let entry = ColumnEntry {
+            pressure,
+            compositions: plates.liquid,
+            vapor_compositions: plates.vapor,
+            ..self.clone()
+        };
+        (entry, plates.failures)
+    }
 }
 
 pub async fn get_column_data(
     settings_state: &State<'_, Mutex<SettingsState>>,
     connection_state: &State<'_, Mutex<CurrentConnection>>,
     data_source_state: &State<'_, Mutex<DataSource>>,
-) -> Result<Arc<ColumnEntry>, String> {
+    previous: Option<Arc<ColumnEntry>>,
+) -> Result<Arc<ColumnEntry>, AppError> {
     let mut ds = data_source_state.lock().await;
 
     match &mut *ds {
-        DataSource::Playback { index, data } => {
-            if let Some(entry) = data.get(*index as usize) {
-                *index += 1;
-                Ok(entry.clone())
-            } else {
-                Err("No more playback data available".into())
-            }
+        DataSource::Playback(playback) => {
+            let entry = playback.next().ok_or(AppError::PlaybackFinished)?;
+            played_entry(
+                settings_state,
+                entry,
+                playback.recompute(),
+                previous.as_deref(),
+            )
+            .await
         }
         DataSource::Live => {
-            // fetch temperatures
-            let temperatures = read_temperatures(settings_state, connection_state.clone())
-                .await
-                .map_err(|e| format!("Failed to read temperatures: {:?}", e))?;
+            // fetch temperatures and pressure
+            let readings = read_sensors(settings_state, connection_state.clone()).await?;
+            let temperatures = readings.temperatures;
+            let pressure = readings.pressure;
 
             // fetch settings
             let settings = {
                 let settings_guard = settings_state.lock().await;
                 settings_guard.settings.clone()
             }
-            .ok_or("No settings found".to_string())?;
-
-            // interpolate temperatures
-            let number_plates = settings.number_plates;
-            let interpolate_temps =
-                interpolate_temperatures(number_plates, temperatures[0], temperatures[1]);
-
-            // calculate compositions
-            let mut compositions: Vec<f64> = Vec::with_capacity(number_plates);
-            let x_0 = 0.5;
-            let tol = 1e-6;
-            let max_iter = 1000;
-
-            const MIN_T: f64 = 70.0;
-            const MAX_T: f64 = 90.0;
-
-            let valid_range = interpolate_temps.first().unwrap_or(&0.0) > &MIN_T
-                && interpolate_temps.last().unwrap_or(&0.0) < &MAX_T;
-            if valid_range {
-                for (i, &temp) in interpolate_temps.iter().enumerate() {
-                    let composition = calculate_composition(x_0, temp, tol, max_iter)
-                        .unwrap_or_else(|e| {
-                            eprintln!("Error calculating composition at index {}: {}", i, e);
-                            if i == 0 {
-                                0.0
-                            } else {
-                                compositions[i - 1]
-                            }
-                        });
-
-                    compositions.push(composition);
-                }
-            }
-
-            println!("Settings: {:?}", settings);
-            println!("Temperatures: {:?}", temperatures);
-            println!("Interpolated temperatures: {:?}", interpolate_temps);
-            println!("Compositions: {:?}", compositions);
+            .ok_or(AppError::no_data("No settings found"))?;
+
+            // fill plates without sensor
+            let interpolate_temps = interpolate_temperatures(&temperatures).ok_or(
+                AppError::settings("plateSensors", "No temperature sensors configured"),
+            )?;
+
+            // calculate compositions, warm started from the previous cycle
+            let plates = plate_compositions(
+                &settings.mixture_model(),
+                pressure,
+                &interpolate_temps,
+                previous.as_ref().map(|entry| entry.compositions.as_slice()),
+            );
+            let compositions = plates.liquid;
+            let vapor_compositions = plates.vapor;
 
             let new_entry = ColumnEntry {
                 timestamp: SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .unwrap()
-                    .as_secs(),
+                    .as_millis() as u64,
+                pressure,
                 temperatures: interpolate_temps,
                 compositions,
+                vapor_compositions,
                 percentage_complete: 0.0,
             };
 
@@ -1,86 +1,305 @@
-use super::data_manager::{get_column_data, DataSource, MeasurementHistory};
+use super::data_manager::{get_column_data, DataSource};
+use super::error::AppError;
+use super::history::MeasurementHistory;
 use super::modbus_serial::CurrentConnection;
-use super::settings::SettingsState;
+use super::playback::Playback;
+use super::run_log::RunLog;
+use super::settings::{AcquisitionSettings, SettingsState};
+use super::supervisor::{report_failure, report_success};
 use crate::commands::data_manager::ColumnEntry;
-use crate::TransmissionState;
+use serde::Serialize;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
-use tokio::time::Duration;
+use tokio::time::{interval, sleep_until, Instant, Interval};
+use tokio_util::sync::CancellationToken;
 
-#[tauri::command]
-pub async fn send_column_data(
-    app_handle: AppHandle,
-    settings_state: State<'_, Mutex<SettingsState>>,
-    connection_state: State<'_, Mutex<CurrentConnection>>,
-    measurement_history_state: State<'_, Mutex<MeasurementHistory>>,
-    data_source_state: State<'_, Mutex<DataSource>>,
-    transmission_state: State<'_, Mutex<TransmissionState>>,
-) -> Result<(), String> {
-    println!("\nInitializing send_column_data...");
-    {
-        // initialize transmission state
-        let mut transmission_state = transmission_state.lock().await;
-        transmission_state.is_running = true;
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AcquisitionState {
+    #[default]
+    Idle,
+    Running,
+    Paused,
+}
+
+/// State of the acquisition task, emitted to the frontend as `acquisition_status`
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquisitionStatus {
+    pub state: AcquisitionState,
+    /// Error that stopped the last acquisition
+    pub last_error: Option<AppError>,
+}
+
+/// Acquisition runs as a background task started by `start_acquisition`.
+/// Pausing or stopping cancels the task at once, resuming spawns a new one that
+/// continues from the same data source.
+#[derive(Default)]
+pub struct Acquisition {
+    status: AcquisitionStatus,
+    cancel: Option<CancellationToken>,
+}
+
+impl Acquisition {
+    fn spawn(&mut self, app_handle: &AppHandle) {
+        let token = CancellationToken::new();
+        self.cancel = Some(token.clone());
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = acquire(&app_handle, &token).await;
+            let acquisition_state = app_handle.state::<Mutex<Acquisition>>();
+            let mut acquisition = acquisition_state.lock().await;
+            // paused or stopped by a command, which already set the state
+            if token.is_cancelled() {
+                return;
+            }
+            acquisition.cancel = None;
+            acquisition.status.last_error = match result {
+                Ok(()) | Err(AppError::PlaybackFinished) => None,
+                Err(e) => {
+                    eprintln!("Acquisition stopped: {}", e);
+                    Some(e)
+                }
+            };
+            acquisition.set_state(&app_handle, AcquisitionState::Idle);
+        });
+    }
+
+    fn cancel(&mut self) {
+        if let Some(token) = self.cancel.take() {
+            token.cancel();
+        }
     }
+
+    fn set_state(&mut self, app_handle: &AppHandle, state: AcquisitionState) {
+        println!("Acquisition {:?} -> {:?}", self.status.state, state);
+        self.status.state = state;
+        emit_acquisition_status(app_handle, &self.status);
+    }
+
+    fn invalid(&self, action: &str) -> AppError {
+        AppError::InvalidState {
+            message: format!(
+                "Cannot {} acquisition while {:?}",
+                action, self.status.state
+            ),
+        }
+    }
+}
+
+/// Acquisition loop, returns when the token is cancelled or the source fails
+async fn acquire(app_handle: &AppHandle, token: &CancellationToken) -> Result<(), AppError> {
+    let settings_state = app_handle.state::<Mutex<SettingsState>>();
+    let connection_state = app_handle.state::<Mutex<CurrentConnection>>();
+    let measurement_history_state = app_handle.state::<Mutex<MeasurementHistory>>();
+    let data_source_state = app_handle.state::<Mutex<DataSource>>();
+    let run_log_state = app_handle.state::<Mutex<RunLog>>();
+
+    let mut schedule: Option<(AcquisitionSettings, Interval)> = None;
+    let mut last_tick: Option<Instant> = None;
     loop {
-        {
-            let transmission_state = transmission_state.lock().await;
-            println!("\nTransmission state: {:?}", transmission_state);
-            if !transmission_state.is_running {
-                return Ok(());
+        let settings = {
+            let current_settings = settings_state.lock().await;
+            current_settings.settings.clone()
+        };
+        let acquisition = settings
+            .as_ref()
+            .map(|settings| settings.acquisition.clone())
+            .unwrap_or_default();
+        let playback_delay = match &*data_source_state.lock().await {
+            DataSource::Playback(playback) => Some(playback.delay(acquisition.sample_period())),
+            DataSource::Live => None,
+        };
+        let is_live = playback_delay.is_none();
+
+        if let Some(delay) = playback_delay {
+            // playback is paced entry by entry, by its speed and the recorded times
+            let due = last_tick.map_or_else(Instant::now, |last| last + delay);
+            tokio::select! {
+                _ = token.cancelled() => return Ok(()),
+                _ = sleep_until(due) => {}
+            };
+            last_tick = Some(due);
+        } else {
+            // the schedule restarts when the period is changed while running
+            let ticker = match &mut schedule {
+                Some((current, ticker)) if *current == acquisition => ticker,
+                _ => {
+                    let mut ticker = interval(acquisition.sample_period());
+                    ticker.set_missed_tick_behavior(acquisition.missed_ticks.behavior());
+                    last_tick = None;
+                    &mut schedule.insert((acquisition, ticker)).1
+                }
+            };
+            let tick = tokio::select! {
+                _ = token.cancelled() => return Ok(()),
+                tick = ticker.tick() => tick,
+            };
+            let period = ticker.period();
+            if let Some(last) = last_tick {
+                let missed = (tick - last).as_millis() / period.as_millis().max(1);
+                if missed > 1 {
+                    eprintln!(
+                        "Acquisition overran the sample period, {} ticks missed",
+                        missed - 1
+                    );
+                }
             }
+            last_tick = Some(tick);
         }
 
-        let data_entry =
-            get_column_data(&settings_state, &connection_state, &data_source_state).await?;
+        let previous = {
+            let history = measurement_history_state.lock().await;
+            history.last()
+        };
+
+        let result = tokio::select! {
+            _ = token.cancelled() => return Ok(()),
+            result = get_column_data(
+                &settings_state,
+                &connection_state,
+                &data_source_state,
+                previous,
+            ) => result,
+        };
+        let data_entry = match result {
+            Ok(entry) => {
+                if is_live {
+                    report_success(app_handle, &connection_state).await;
+                }
+                entry
+            }
+            Err(e) if is_live => {
+                // keep acquiring while the supervisor restores the link, a
+                // reconnect in progress is abandoned when the task is cancelled
+                eprintln!("Acquisition cycle failed: {}", e);
+                report_failure(app_handle, &settings_state, &connection_state, e, token).await?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
 
         {
+            let history_settings = settings
+                .as_ref()
+                .map(|settings| settings.history.clone())
+                .unwrap_or_default();
             let mut history = measurement_history_state.lock().await;
-            history.history.push(data_entry.clone());
+            if is_live {
+                history.push(data_entry.clone(), &history_settings);
+            } else {
+                history.push_played(data_entry.clone(), &history_settings);
+            }
+        }
+        if is_live {
+            let mut run_log = run_log_state.lock().await;
+            if let Err(e) = run_log.append(app_handle, settings.as_ref(), &data_entry) {
+                eprintln!("Failed to log entry: {}", e);
+            }
         }
 
         println!("Emitting data: {:?}", data_entry);
-        emit_column_data(&app_handle, data_entry).await?;
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        emit_column_data(app_handle, data_entry).await?;
+    }
+}
+
+/// Starts acquiring from the current data source in the background
+#[tauri::command]
+pub async fn start_acquisition(
+    app_handle: AppHandle,
+    acquisition_state: State<'_, Mutex<Acquisition>>,
+) -> Result<AcquisitionStatus, AppError> {
+    let mut acquisition = acquisition_state.lock().await;
+    if acquisition.status.state != AcquisitionState::Idle {
+        return Err(acquisition.invalid("start"));
+    }
+    acquisition.status.last_error = None;
+    acquisition.spawn(&app_handle);
+    acquisition.set_state(&app_handle, AcquisitionState::Running);
+    Ok(acquisition.status.clone())
+}
+
+#[tauri::command]
+pub async fn pause_acquisition(
+    app_handle: AppHandle,
+    acquisition_state: State<'_, Mutex<Acquisition>>,
+) -> Result<AcquisitionStatus, AppError> {
+    let mut acquisition = acquisition_state.lock().await;
+    if acquisition.status.state != AcquisitionState::Running {
+        return Err(acquisition.invalid("pause"));
     }
+    acquisition.cancel();
+    acquisition.set_state(&app_handle, AcquisitionState::Paused);
+    Ok(acquisition.status.clone())
 }
 
 #[tauri::command]
-pub async fn cancel_column_data(
-    transmission_state: State<'_, Mutex<TransmissionState>>,
+pub async fn resume_acquisition(
+    app_handle: AppHandle,
+    acquisition_state: State<'_, Mutex<Acquisition>>,
+) -> Result<AcquisitionStatus, AppError> {
+    let mut acquisition = acquisition_state.lock().await;
+    if acquisition.status.state != AcquisitionState::Paused {
+        return Err(acquisition.invalid("resume"));
+    }
+    acquisition.spawn(&app_handle);
+    acquisition.set_state(&app_handle, AcquisitionState::Running);
+    Ok(acquisition.status.clone())
+}
+
+/// Stops the acquisition, closes the current run and discards the playback data
+#[tauri::command]
+pub async fn stop_acquisition(
+    app_handle: AppHandle,
+    acquisition_state: State<'_, Mutex<Acquisition>>,
     data_source_state: State<'_, Mutex<DataSource>>,
-) -> Result<(), String> {
-    println!("Canceling column data");
-    let mut transmission_state = transmission_state.lock().await;
-    transmission_state.is_running = false;
+    run_log_state: State<'_, Mutex<RunLog>>,
+) -> Result<AcquisitionStatus, AppError> {
+    println!("Stopping acquisition");
+    let status = stop(&app_handle, &acquisition_state).await;
+    run_log_state.lock().await.finish();
     let mut ds = data_source_state.lock().await;
 
     match &mut *ds {
-        DataSource::Playback { index, data } => {
-            *index = 0;
-            *data = Vec::new();
-        }
+        DataSource::Playback(playback) => *playback = Playback::default(),
         DataSource::Live => {}
     }
-    Ok(())
+    Ok(status)
 }
 
 #[tauri::command]
-pub async fn pause_column_data(
-    transmission_state: State<'_, Mutex<TransmissionState>>,
-) -> Result<(), String> {
-    println!("Pausing column data");
-    let mut transmission_state = transmission_state.lock().await;
-    transmission_state.is_running = false;
-    Ok(())
+pub async fn acquisition_status(
+    acquisition_state: State<'_, Mutex<Acquisition>>,
+) -> Result<AcquisitionStatus, AppError> {
+    let acquisition = acquisition_state.lock().await;
+    Ok(acquisition.status.clone())
+}
+
+/// Cancels the acquisition task, if any, and returns to idle
+pub async fn stop(
+    app_handle: &AppHandle,
+    acquisition_state: &State<'_, Mutex<Acquisition>>,
+) -> AcquisitionStatus {
+    let mut acquisition = acquisition_state.lock().await;
+    acquisition.cancel();
+    if acquisition.status.state != AcquisitionState::Idle {
+        acquisition.set_state(app_handle, AcquisitionState::Idle);
+    }
+    acquisition.status.clone()
+}
+
+fn emit_acquisition_status(app_handle: &AppHandle, status: &AcquisitionStatus) {
+    if let Err(e) = app_handle.emit("acquisition_status", status) {
+        eprintln!("Failed to emit acquisition status: {}", e);
+    }
 }
 
-async fn emit_column_data(
+pub async fn emit_column_data(
     app_handle: &tauri::AppHandle,
     entry: Arc<ColumnEntry>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     app_handle
         .emit("column_data", entry)
-        .map_err(|e| format!("Failed to emit data: {}", e))
+        .map_err(AppError::internal)
 }
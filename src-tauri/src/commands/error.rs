@@ -0,0 +1,191 @@
+use super::solver::SolverError;
+use rodbus::RequestError;
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned by every command. Serialized with a `code` tag so the frontend
+/// can branch on it, the remaining fields give the context of the failure.
+#[derive(Serialize, Debug, Clone)]
+#[serde(
+    tag = "code",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AppError {
+    NotConnected,
+    AlreadyConnected,
+    /// The link could not be opened or was lost
+    Connection {
+        target: String,
+        message: String,
+    },
+    /// The device did not answer in time
+    Timeout {
+        context: String,
+    },
+    /// The device answered with a Modbus exception
+    ModbusException {
+        context: String,
+        exception: u8,
+        message: String,
+    },
+    /// Malformed or unexpected Modbus response, or an invalid request
+    Modbus {
+        context: String,
+        message: String,
+    },
+    SettingsInvalid {
+        field: Option<String>,
+        message: String,
+    },
+    /// The settings failed validation, with every offending field
+    SettingsRejected {
+        errors: Vec<FieldError>,
+    },
+    FileIo {
+        path: String,
+        message: String,
+    },
+    Parse {
+        context: String,
+        message: String,
+    },
+    Solver {
+        plate: Option<usize>,
+        error: SolverError,
+    },
+    /// The imported data has no entries left to play
+    PlaybackFinished,
+    /// The command is not allowed in the current acquisition state
+    InvalidState {
+        message: String,
+    },
+    /// Measurements or settings needed by the command are missing
+    NoData {
+        message: String,
+    },
+    Internal {
+        message: String,
+    },
+}
+
+/// Problem with a single settings field. `field` is the camelCase path of the
+/// field such as `network.host` or `plateSensors[2].address`.
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl AppError {
+    /// Classifies a failed Modbus request made while doing `context`
+    pub fn modbus(context: impl Into<String>, err: RequestError) -> Self {
+        let context = context.into();
+        match err {
+            RequestError::ResponseTimeout => AppError::Timeout { context },
+            RequestError::Exception(exception) => AppError::ModbusException {
+                context,
+                exception: exception.into(),
+                message: exception.to_string(),
+            },
+            RequestError::NoConnection | RequestError::Shutdown | RequestError::Io(_) => {
+                AppError::Connection {
+                    target: context,
+                    message: err.to_string(),
+                }
+            }
+            err => AppError::Modbus {
+                context,
+                message: err.to_string(),
+            },
+        }
+    }
+
+    pub fn settings(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::SettingsInvalid {
+            field: Some(field.into()),
+            message: message.into(),
+        }
+    }
+
+    pub fn file(path: impl Into<String>, err: impl fmt::Display) -> Self {
+        AppError::FileIo {
+            path: path.into(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn parse(context: impl Into<String>, err: impl fmt::Display) -> Self {
+        AppError::Parse {
+            context: context.into(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn no_data(message: impl Into<String>) -> Self {
+        AppError::NoData {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(err: impl fmt::Display) -> Self {
+        AppError::Internal {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotConnected => write!(f, "No active connection"),
+            AppError::AlreadyConnected => write!(f, "Already connected"),
+            AppError::Connection { target, message } => {
+                write!(f, "Connection error ({}): {}", target, message)
+            }
+            AppError::Timeout { context } => write!(f, "Timeout {}", context),
+            AppError::ModbusException {
+                context,
+                exception,
+                message,
+            } => write!(f, "Modbus exception {} {}: {}", exception, context, message),
+            AppError::Modbus { context, message } => {
+                write!(f, "Modbus error {}: {}", context, message)
+            }
+            AppError::SettingsInvalid { field, message } => match field {
+                Some(field) => write!(f, "Invalid setting {}: {}", field, message),
+                None => write!(f, "Invalid settings: {}", message),
+            },
+            AppError::SettingsRejected { errors } => {
+                write!(f, "Invalid settings: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{} {}", error.field, error.message)?;
+                }
+                Ok(())
+            }
+            AppError::FileIo { path, message } => write!(f, "File error {}: {}", path, message),
+            AppError::Parse { context, message } => {
+                write!(f, "Parse error {}: {}", context, message)
+            }
+            AppError::Solver { plate, error } => match plate {
+                Some(plate) => write!(f, "Solver error at plate {}: {}", plate, error),
+                None => write!(f, "Solver error: {}", error),
+            },
+            AppError::PlaybackFinished => write!(f, "No more playback data available"),
+            AppError::InvalidState { message } => write!(f, "{}", message),
+            AppError::NoData { message } => write!(f, "{}", message),
+            AppError::Internal { message } => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<SolverError> for AppError {
+    fn from(error: SolverError) -> Self {
+        AppError::Solver { plate: None, error }
+    }
+}
@@ -0,0 +1,323 @@
+use super::data_manager::ColumnEntry;
+use super::error::AppError;
+use super::settings::HistorySettings;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+
+/// Points returned by `query_history` when no resolution is requested
+const DEFAULT_MAX_POINTS: u64 = 500;
+
+/// Minimum, maximum and mean of a value over a bucket
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl Stats {
+    fn of(value: f64) -> Self {
+        Self {
+            min: value,
+            max: value,
+            mean: value,
+        }
+    }
+
+    /// Combines two aggregates, weighting the means by their sample counts
+    fn merge(self, other: Self, samples: usize, other_samples: usize) -> Self {
+        let total = (samples + other_samples).max(1) as f64;
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            mean: (self.mean * samples as f64 + other.mean * other_samples as f64) / total,
+        }
+    }
+}
+
+/// Aggregate of the entries acquired in `[start, end)`, timestamps in milliseconds
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryBucket {
+    pub start: u64,
+    pub end: u64,
+    pub samples: usize,
+    pub pressure: Stats,
+    pub temperatures: Vec<Stats>,
+    pub compositions: Vec<Stats>,
+}
+
+impl HistoryBucket {
+    fn new(start: u64, end: u64, entry: &ColumnEntry) -> Self {
+        Self {
+            start,
+            end,
+            samples: 1,
+            pressure: Stats::of(entry.pressure),
+            temperatures: entry.temperatures.iter().copied().map(Stats::of).collect(),
+            compositions: entry.compositions.iter().copied().map(Stats::of).collect(),
+        }
+    }
+
+    fn add(&mut self, entry: &ColumnEntry) {
+        let start = self.start;
+        self.merge(&HistoryBucket::new(start, start, entry));
+    }
+
+    fn merge(&mut self, other: &HistoryBucket) {
+        let (samples, other_samples) = (self.samples, other.samples);
+        let merge_all = |stats: &mut Vec<Stats>, others: &[Stats]| {
+            for (i, &other) in others.iter().enumerate() {
+                match stats.get_mut(i) {
+                    Some(stat) => *stat = stat.merge(other, samples, other_samples),
+                    // plates added while the bucket was filling
+                    None => stats.push(other),
+                }
+            }
+        };
+        merge_all(&mut self.temperatures, &other.temperatures);
+        merge_all(&mut self.compositions, &other.compositions);
+        self.pressure = self.pressure.merge(other.pressure, samples, other_samples);
+        self.start = self.start.min(other.start);
+        self.end = self.end.max(other.end);
+        self.samples += other.samples;
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryWindow {
+    /// Width in milliseconds of every point
+    pub resolution: u64,
+    pub points: Vec<HistoryBucket>,
+}
+
+/// History kept in memory for the session. Raw entries are kept for the raw
+/// retention and every entry is also aggregated into fixed width buckets kept
+/// for much longer, so memory stays bounded on multi-day runs. The full run is
+/// in the run log on disk.
+#[derive(Default)]
+pub struct MeasurementHistory {
+    entries: VecDeque<Arc<ColumnEntry>>,
+    buckets: VecDeque<HistoryBucket>,
+    /// Bucket being filled with the latest entries
+    pending: Option<HistoryBucket>,
+    /// Timestamp of the newest raw entry dropped by the retention
+    last_evicted: Option<u64>,
+}
+
+impl MeasurementHistory {
+    /// Adds an acquired entry. An entry older than the last one, as after the
+    /// clock was set back, is dropped so the recorded hours are kept.
+    pub fn push(&mut self, entry: Arc<ColumnEntry>, settings: &HistorySettings) {
+        if self.is_out_of_order(&entry) {
+            eprintln!(
+                "Dropping entry at {} older than the last one in the history",
+                entry.timestamp
+            );
+            return;
+        }
+        self.append(entry, settings);
+    }
+
+    /// Adds a played entry. An entry older than the last one, as when a playback
+    /// is looped or moved back, starts the history over.
+    pub fn push_played(&mut self, entry: Arc<ColumnEntry>, settings: &HistorySettings) {
+        if self.is_out_of_order(&entry) {
+            self.clear();
+        }
+        self.append(entry, settings);
+    }
+
+    fn is_out_of_order(&self, entry: &ColumnEntry) -> bool {
+        self.entries
+            .back()
+            .is_some_and(|last| entry.timestamp < last.timestamp)
+    }
+
+    fn append(&mut self, entry: Arc<ColumnEntry>, settings: &HistorySettings) {
+        let width = settings.bucket_seconds.max(1) * 1000;
+        let start = entry.timestamp - entry.timestamp % width;
+        match &mut self.pending {
+            Some(bucket) if bucket.start == start => bucket.add(&entry),
+            _ => {
+                let bucket = HistoryBucket::new(start, start + width, &entry);
+                if let Some(done) = self.pending.replace(bucket) {
+                    self.buckets.push_back(done);
+                }
+            }
+        }
+
+        let newest = entry.timestamp;
+        self.entries.push_back(entry);
+        let raw_cutoff = newest.saturating_sub(settings.raw_retention_minutes * 60_000);
+        while self
+            .entries
+            .front()
+            .is_some_and(|entry| entry.timestamp < raw_cutoff)
+        {
+            self.last_evicted = self.entries.pop_front().map(|entry| entry.timestamp);
+        }
+        let bucket_cutoff = newest.saturating_sub(settings.bucket_retention_hours * 3_600_000);
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.end <= bucket_cutoff)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    pub fn last(&self) -> Option<Arc<ColumnEntry>> {
+        self.entries.back().cloned()
+    }
+
+    /// Raw entries still retained
+    pub fn entries(&self) -> Vec<Arc<ColumnEntry>> {
+        self.entries.iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Aggregates `[start, end)` into points `resolution` milliseconds wide. Raw
+    /// entries are used where retained and the buckets before them, so points
+    /// older than the raw retention are never finer than the bucket width.
+    pub fn query(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        resolution: u64,
+    ) -> Vec<HistoryBucket> {
+        let resolution = resolution.max(1);
+        let in_window =
+            |time: u64| start.is_none_or(|start| time >= start) && end.is_none_or(|end| time < end);
+
+        let mut points: Vec<HistoryBucket> = Vec::new();
+        let mut add = |mut bucket: HistoryBucket| {
+            let bin = bucket.start - bucket.start % resolution;
+            match points.last_mut() {
+                Some(point) if point.start == bin => point.merge(&bucket),
+                _ => {
+                    bucket.start = bin;
+                    bucket.end = bucket.end.max(bin + resolution);
+                    points.push(bucket);
+                }
+            }
+        };
+
+        // buckets holding evicted entries, the last one also holds retained entries
+        let older: Vec<&HistoryBucket> = self
+            .buckets
+            .iter()
+            .chain(self.pending.iter())
+            .filter(|bucket| {
+                self.last_evicted
+                    .is_some_and(|evicted| bucket.start <= evicted)
+            })
+            .collect();
+        let boundary = older.last().map(|bucket| bucket.end).unwrap_or_default();
+        for bucket in older.into_iter().filter(|bucket| in_window(bucket.start)) {
+            add(bucket.clone());
+        }
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.timestamp >= boundary && in_window(entry.timestamp))
+        {
+            add(HistoryBucket::new(entry.timestamp, entry.timestamp, entry));
+        }
+        points
+    }
+
+    fn span(&self) -> Option<(u64, u64)> {
+        let first = self
+            .buckets
+            .front()
+            .or(self.pending.as_ref())
+            .map(|bucket| bucket.start)?;
+        let last = self.entries.back().map(|entry| entry.timestamp)?;
+        Some((first, last + 1))
+    }
+}
+
+/// Returns the history between `start` and `end` (milliseconds since the epoch,
+/// the whole history when omitted) aggregated every `resolution` milliseconds. Without
+/// a resolution the window is split into at most `maxPoints` points.
+#[tauri::command]
+pub async fn query_history(
+    history_state: State<'_, Mutex<MeasurementHistory>>,
+    start: Option<u64>,
+    end: Option<u64>,
+    resolution: Option<u64>,
+    max_points: Option<u64>,
+) -> Result<HistoryWindow, AppError> {
+    let history = history_state.lock().await;
+    let resolution = match resolution {
+        Some(resolution) => resolution.max(1),
+        None => {
+            let (first, last) = history
+                .span()
+                .ok_or(AppError::no_data("No history recorded"))?;
+            let span = end.unwrap_or(last).saturating_sub(start.unwrap_or(first));
+            span.div_ceil(max_points.unwrap_or(DEFAULT_MAX_POINTS).max(1))
+                .max(1)
+        }
+    };
+    Ok(HistoryWindow {
+        resolution,
+        points: history.query(start, end, resolution),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64) -> Arc<ColumnEntry> {
+        Arc::new(ColumnEntry {
+            timestamp,
+            pressure: 585.0,
+            temperatures: vec![80.0, 90.0],
+            compositions: Vec::new(),
+            vapor_compositions: Vec::new(),
+            percentage_complete: 0.0,
+        })
+    }
+
+    fn timestamps(history: &MeasurementHistory) -> Vec<u64> {
+        history
+            .entries()
+            .iter()
+            .map(|entry| entry.timestamp)
+            .collect()
+    }
+
+    #[test]
+    fn acquired_entry_from_the_past_is_dropped() {
+        let settings = HistorySettings::default();
+        let mut history = MeasurementHistory::default();
+        for timestamp in [10_000, 11_000, 12_000] {
+            history.push(entry(timestamp), &settings);
+        }
+        history.push(entry(5_000), &settings);
+        history.push(entry(13_000), &settings);
+        assert_eq!(timestamps(&history), [10_000, 11_000, 12_000, 13_000]);
+    }
+
+    #[test]
+    fn played_entry_from_the_past_starts_over() {
+        let settings = HistorySettings::default();
+        let mut history = MeasurementHistory::default();
+        for timestamp in [10_000, 11_000, 12_000] {
+            history.push_played(entry(timestamp), &settings);
+        }
+        history.push_played(entry(5_000), &settings);
+        assert_eq!(timestamps(&history), [5_000]);
+    }
+}
@@ -0,0 +1,164 @@
+use super::error::AppError;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Version of the settings files written by this build
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Upgrade of a settings object from version `i` to `i + 1`. Fields missing from
+/// older files are filled in from the serde defaults, migrations only handle
+/// values that changed meaning or are no longer accepted.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SETTINGS_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Version of a settings file, 0 for files written before versions were added
+pub fn settings_version(settings: &Value) -> u32 {
+    settings
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version as u32)
+}
+
+/// Brings `settings` from `version` to `SETTINGS_VERSION`
+pub fn migrate(settings: &mut Value, version: u32) -> Result<(), AppError> {
+    let Some(object) = settings.as_object_mut() else {
+        return Err(AppError::parse(
+            "migrating settings",
+            "settings file is not a JSON object",
+        ));
+    };
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        println!("Migrating settings from version {} to {}", from, from + 1);
+        migration(object);
+        object.insert("version".to_string(), json!(from + 1));
+    }
+    Ok(())
+}
+
+/// Copies the settings file next to it as `settings.v<version>.bak.json` before
+/// it is migrated or overwritten by an older version
+pub fn backup_settings(path: &Path, version: u32) -> Result<PathBuf, AppError> {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("settings");
+    let backup = path.with_file_name(format!("{}.v{}.bak.json", stem, version));
+    fs::copy(path, &backup).map_err(|e| AppError::file(backup.display().to_string(), e))?;
+    println!("Settings backed up to {}", backup.display());
+    Ok(backup)
+}
+
+/// Unversioned files were written by the derived default, all zeros, or by the
+/// frontend, which allowed a single plate. Zeroed fields get the current
+/// defaults and the column at least 2 plates.
+fn v0_to_v1(settings: &mut Map<String, Value>) {
+    let mut replace_zero = |field: &str, value: Value| {
+        if settings.get(field).and_then(Value::as_u64).unwrap_or(0) == 0 {
+            settings.insert(field.to_string(), value);
+        }
+    };
+    replace_zero("baudrate", json!(9600));
+    replace_zero("count", json!(1));
+    replace_zero("timeout", json!(1000));
+    replace_zero("unitId", json!(10));
+
+    let plates = settings
+        .get("numberPlates")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if plates < 2 {
+        settings.insert("numberPlates".to_string(), json!(2));
+    }
+
+    // both sensors on register 0 is the untouched default
+    let address = |name: &str| {
+        settings
+            .get("temperatureAddress")
+            .and_then(|addresses| addresses.get(name))
+            .and_then(Value::as_u64)
+            .unwrap_or(0)
+    };
+    if address("top") == 0 && address("bottom") == 0 {
+        settings.insert(
+            "temperatureAddress".to_string(),
+            json!({ "top": 101, "bottom": 100 }),
+        );
+    }
+}
+
+/// `count` was never read, every sensor reads as many registers as its format
+/// needs
+fn v1_to_v2(settings: &mut Map<String, Value>) {
+    settings.remove("count");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::Settings;
+
+    #[test]
+    fn migrates_unversioned_defaults() {
+        let mut settings = json!({
+            "usbPort": "/dev/ttyUSB0",
+            "baudrate": 0,
+            "temperatureAddress": { "top": 0, "bottom": 0 },
+            "count": 0,
+            "timeout": 0,
+            "unitId": 0,
+            "numberPlates": 1,
+        });
+        let version = settings_version(&settings);
+        assert_eq!(version, 0);
+        migrate(&mut settings, version).unwrap();
+
+        assert_eq!(settings_version(&settings), SETTINGS_VERSION);
+        assert_eq!(settings["usbPort"], "/dev/ttyUSB0");
+        assert_eq!(settings["baudrate"], 9600);
+        assert_eq!(settings["timeout"], 1000);
+        assert_eq!(settings["unitId"], 10);
+        assert_eq!(settings["numberPlates"], 2);
+        assert_eq!(
+            settings["temperatureAddress"],
+            json!({ "top": 101, "bottom": 100 })
+        );
+        assert!(settings.get("count").is_none());
+        let settings: Settings = serde_json::from_value(settings).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn keeps_configured_values() {
+        let mut settings = json!({
+            "baudrate": 19200,
+            "temperatureAddress": { "top": 0, "bottom": 1 },
+            "count": 4,
+            "timeout": 500,
+            "unitId": 3,
+            "numberPlates": 8,
+        });
+        migrate(&mut settings, 0).unwrap();
+
+        assert_eq!(settings["baudrate"], 19200);
+        assert_eq!(settings["timeout"], 500);
+        assert_eq!(settings["unitId"], 3);
+        assert_eq!(settings["numberPlates"], 8);
+        assert_eq!(
+            settings["temperatureAddress"],
+            json!({ "top": 0, "bottom": 1 })
+        );
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let mut settings = json!({ "version": SETTINGS_VERSION, "timeout": 0 });
+        let before = settings.clone();
+        migrate(&mut settings, SETTINGS_VERSION).unwrap();
+        assert_eq!(settings, before);
+    }
+
+    #[test]
+    fn rejects_non_object_settings() {
+        assert!(migrate(&mut json!([1, 2]), 0).is_err());
+    }
+}
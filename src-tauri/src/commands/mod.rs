@@ -1,6 +1,21 @@
+pub mod activity;
 pub mod calculations;
+pub mod csv_file;
 pub mod data_manager;
 pub mod emitter;
+pub mod error;
+pub mod history;
+pub mod migrations;
 pub mod modbus_serial;
+pub mod playback;
+pub mod registers;
+pub mod rtu_over_tcp;
+pub mod run_log;
 pub mod settings;
+pub mod simulator;
+pub mod solver;
+pub mod supervisor;
+pub mod thermodynamics;
 pub mod utils;
+pub mod validation;
+pub mod xlsx_file;
@@ -1,13 +1,17 @@
-use crate::TransmissionState;
-
 use super::data_manager::DataSource;
-use super::emitter::cancel_column_data;
-use super::settings::SettingsState;
+use super::emitter::{self, Acquisition};
+use super::error::AppError;
+use super::rtu_over_tcp::RtuOverTcpClient;
+use super::run_log::RunLog;
+use super::settings::{ProbeFunction, Settings, SettingsState, Transport};
+use super::simulator::ColumnSimulator;
+use super::supervisor::{emit_connection_status, LinkHealth};
 use rodbus::client::*;
 use rodbus::*;
 use serde::Serialize;
-use std::sync::Arc;
-use tauri::State;
+use std::net::IpAddr;
+use std::sync::{Arc, MutexGuard, PoisonError};
+use tauri::{AppHandle, State};
 use tokio::sync::Mutex;
 
 #[derive(Serialize, Debug)]
@@ -16,18 +20,379 @@ pub struct RegisterResponse {
     pub value: u16,
 }
 
+impl From<Indexed<u16>> for RegisterResponse {
+    fn from(register: Indexed<u16>) -> Self {
+        Self {
+            index: register.index,
+            value: register.value,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct CoilResponse {
+    index: u16,
+    pub value: bool,
+}
+
+impl From<Indexed<bool>> for CoilResponse {
+    fn from(coil: Indexed<bool>) -> Self {
+        Self {
+            index: coil.index,
+            value: coil.value,
+        }
+    }
+}
+
+/// Range acknowledged by the device after a block write
+#[derive(Serialize, Debug)]
+pub struct WriteMultipleResponse {
+    pub start: u16,
+    pub count: u16,
+}
+
+impl From<AddressRange> for WriteMultipleResponse {
+    fn from(range: AddressRange) -> Self {
+        Self {
+            start: range.start,
+            count: range.count,
+        }
+    }
+}
+
+/// Client of the configured transport. Serial RTU and Modbus TCP run on rodbus
+/// channels, RTU framed over TCP goes through `RtuOverTcpClient` and the
+/// simulated transport is answered in process by `ColumnSimulator`.
+pub enum ModbusClient {
+    Channel(Channel),
+    RtuOverTcp(RtuOverTcpClient),
+    Simulated(SharedSimulator),
+    /// Shut down for a reconnect, every request fails at once
+    Closed,
+}
+
+/// Simulated column shared with the client reopened after a lost link, so a
+/// reconnect does not restart it
+pub type SharedSimulator = Arc<std::sync::Mutex<ColumnSimulator>>;
+
+fn lock_simulator(simulator: &SharedSimulator) -> MutexGuard<'_, ColumnSimulator> {
+    simulator.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ModbusClient {
+    fn open(settings: &Settings, simulator: Option<SharedSimulator>) -> Result<Self, AppError> {
+        let client = match settings.transport {
+            Transport::RtuSerial => {
+                let serial_settings = settings.serial.to_serial_settings(settings.baudrate)?;
+                ModbusClient::Channel(client::spawn_rtu_client_task(
+                    &settings.usb_port,
+                    serial_settings,
+                    1,
+                    default_retry_strategy(),
+                    DecodeLevel::default(),
+                    None,
+                ))
+            }
+            Transport::Tcp => {
+                let network = &settings.network;
+                let host = match network.host.parse::<IpAddr>() {
+                    Ok(ip) => HostAddr::ip(ip, network.port),
+                    Err(_) => HostAddr::dns(network.host.clone(), network.port),
+                };
+                ModbusClient::Channel(client::spawn_tcp_client_task(
+                    host,
+                    1,
+                    default_retry_strategy(),
+                    DecodeLevel::default(),
+                    None,
+                ))
+            }
+            Transport::RtuOverTcp => ModbusClient::RtuOverTcp(RtuOverTcpClient::new(
+                &settings.network.host,
+                settings.network.port,
+            )),
+            Transport::Simulated => ModbusClient::Simulated(simulator.unwrap_or_else(|| {
+                Arc::new(std::sync::Mutex::new(ColumnSimulator::new(settings)))
+            })),
+        };
+        Ok(client)
+    }
+
+    /// Human readable target of the connection, e.g. `COM3 9600 8E1`
+    pub fn describe(settings: &Settings) -> String {
+        match settings.transport {
+            Transport::RtuSerial => format!(
+                "{} {} {}",
+                settings.usb_port, settings.baudrate, settings.serial
+            ),
+            Transport::Tcp => format!("{}:{}", settings.network.host, settings.network.port),
+            Transport::RtuOverTcp => {
+                format!("{}:{} (RTU)", settings.network.host, settings.network.port)
+            }
+            Transport::Simulated => "simulated column".into(),
+        }
+    }
+
+    /// Starts the rodbus channel task, the RTU over TCP client connects on its first request
+    async fn enable(&self) -> Result<(), Shutdown> {
+        match self {
+            ModbusClient::Channel(channel) => channel.enable().await,
+            ModbusClient::RtuOverTcp(_) | ModbusClient::Simulated(_) => Ok(()),
+            ModbusClient::Closed => Err(Shutdown),
+        }
+    }
+
+    /// Simulated column answering this client
+    pub fn simulator(&self) -> Option<SharedSimulator> {
+        match self {
+            ModbusClient::Simulated(simulator) => Some(simulator.clone()),
+            _ => None,
+        }
+    }
+
+    /// Releases the link so a new client can open the same port, the rodbus
+    /// channel stops retrying before it is dropped
+    pub async fn shutdown(&mut self) {
+        if let ModbusClient::Channel(channel) = self {
+            if let Err(e) = channel.disable().await {
+                eprintln!("Failed to disable channel: {}", e);
+            }
+        }
+        *self = ModbusClient::Closed;
+    }
+
+    /// Opens the configured transport and checks the link
+    pub async fn connect(settings: &Settings) -> Result<Self, AppError> {
+        Self::open(settings, None)?.check(settings).await
+    }
+
+    /// Reopens the link after it was lost, the simulated column carries on
+    /// from the state it had
+    pub async fn reconnect(
+        settings: &Settings,
+        simulator: Option<SharedSimulator>,
+    ) -> Result<Self, AppError> {
+        Self::open(settings, simulator)?.check(settings).await
+    }
+
+    /// Checks the link with the probe request, retrying with backoff as configured
+    async fn check(mut self, settings: &Settings) -> Result<Self, AppError> {
+        let target = Self::describe(settings);
+
+        if let Err(err) = self.enable().await {
+            return Err(AppError::Connection {
+                target,
+                message: format!("Failed to enable connection: {}", err),
+            });
+        }
+
+        let probe = &settings.probe;
+        let (unit_id, address, count) = settings.probe_target();
+        let params = RequestParam::new(
+            UnitId::new(unit_id),
+            std::time::Duration::from_millis(probe.timeout_ms),
+        );
+        let range = AddressRange::try_from(address, count)
+            .map_err(|e| AppError::settings("probe.address", e.to_string()))?;
+        let attempts = probe.retries + 1;
+        let mut backoff = std::time::Duration::from_millis(probe.backoff_ms);
+        let mut last_error = RequestError::NoConnection;
+
+        for attempt in 1..=attempts {
+            match self.probe(probe.function, params, range).await {
+                Ok(_) => return Ok(self),
+                Err(err) => {
+                    println!("Attempt {}/{} failed: {:?}", attempt, attempts, err);
+                    last_error = err;
+                    if attempt < attempts {
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                    }
+                }
+            }
+        }
+
+        Err(AppError::Connection {
+            target,
+            message: format!(
+                "No response from unit {} at address {} after {} attempts: {}",
+                unit_id, address, attempts, last_error
+            ),
+        })
+    }
+
+    /// Sends the probe request, only whether the device answered matters
+    async fn probe(
+        &mut self,
+        function: ProbeFunction,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<(), RequestError> {
+        match function {
+            ProbeFunction::Coils => self.read_coils(param, range).await.map(|_| ()),
+            ProbeFunction::DiscreteInputs => {
+                self.read_discrete_inputs(param, range).await.map(|_| ())
+            }
+            ProbeFunction::HoldingRegisters => {
+                self.read_holding_registers(param, range).await.map(|_| ())
+            }
+            ProbeFunction::InputRegisters => {
+                self.read_input_registers(param, range).await.map(|_| ())
+            }
+        }
+    }
+
+    pub async fn read_coils(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.read_coils(param, range).await,
+            ModbusClient::RtuOverTcp(client) => client.read_coils(param, range).await,
+            ModbusClient::Simulated(column) => lock_simulator(column).read_coils(param, range),
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn read_discrete_inputs(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.read_discrete_inputs(param, range).await,
+            ModbusClient::RtuOverTcp(client) => client.read_discrete_inputs(param, range).await,
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).read_discrete_inputs(param, range)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn read_holding_registers(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.read_holding_registers(param, range).await,
+            ModbusClient::RtuOverTcp(client) => client.read_holding_registers(param, range).await,
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).read_holding_registers(param, range)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn read_input_registers(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.read_input_registers(param, range).await,
+            ModbusClient::RtuOverTcp(client) => client.read_input_registers(param, range).await,
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).read_input_registers(param, range)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn write_single_coil(
+        &mut self,
+        param: RequestParam,
+        request: Indexed<bool>,
+    ) -> Result<Indexed<bool>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.write_single_coil(param, request).await,
+            ModbusClient::RtuOverTcp(client) => client.write_single_coil(param, request).await,
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).write_single_coil(param, request)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn write_single_register(
+        &mut self,
+        param: RequestParam,
+        request: Indexed<u16>,
+    ) -> Result<Indexed<u16>, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => channel.write_single_register(param, request).await,
+            ModbusClient::RtuOverTcp(client) => client.write_single_register(param, request).await,
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).write_single_register(param, request)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn write_multiple_coils(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        values: Vec<bool>,
+    ) -> Result<AddressRange, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => {
+                let request = WriteMultiple::from(start, values)?;
+                channel.write_multiple_coils(param, request).await
+            }
+            ModbusClient::RtuOverTcp(client) => {
+                client.write_multiple_coils(param, start, &values).await
+            }
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).write_multiple_coils(param, start, values)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+
+    pub async fn write_multiple_registers(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        values: Vec<u16>,
+    ) -> Result<AddressRange, RequestError> {
+        match self {
+            ModbusClient::Channel(channel) => {
+                let request = WriteMultiple::from(start, values)?;
+                channel.write_multiple_registers(param, request).await
+            }
+            ModbusClient::RtuOverTcp(client) => {
+                client.write_multiple_registers(param, start, &values).await
+            }
+            ModbusClient::Simulated(column) => {
+                lock_simulator(column).write_multiple_registers(param, start, values)
+            }
+            ModbusClient::Closed => Err(RequestError::NoConnection),
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct CurrentConnection {
-    connection: Option<Arc<Mutex<Channel>>>,
+    connection: Option<Arc<Mutex<ModbusClient>>>,
+    pub health: LinkHealth,
 }
 
 impl CurrentConnection {
-    fn set_connection(&mut self, channel: Channel) {
-        self.connection = Some(Arc::new(Mutex::new(channel)));
+    pub fn set_connection(&mut self, client: ModbusClient) {
+        self.connection = Some(Arc::new(Mutex::new(client)));
+        self.health = LinkHealth::connected();
     }
 
-    fn clear_connection(&mut self) {
+    pub fn clear_connection(&mut self) {
         self.connection = None;
+        self.health = LinkHealth::default();
+    }
+
+    /// Client of the connection, shared with the requests in flight
+    pub fn client(&self) -> Option<Arc<Mutex<ModbusClient>>> {
+        self.connection.clone()
     }
 
     pub fn is_connected(&self) -> bool {
@@ -37,89 +402,60 @@ impl CurrentConnection {
 
 #[tauri::command]
 pub async fn connect_modbus(
+    app_handle: AppHandle,
     connection: State<'_, Mutex<CurrentConnection>>,
     settings_state: State<'_, Mutex<SettingsState>>,
     data_source_state: State<'_, Mutex<DataSource>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut current_connection = connection.lock().await;
     let current_settings = settings_state.lock().await;
-    if let Some(current_settings) = &current_settings.settings {
-        if current_connection.is_connected() {
-            return Err("Already connected".into());
-        }
-
-        let settings = SerialSettings {
-            baud_rate: current_settings.baudrate,
-            data_bits: rodbus::DataBits::Eight,
-            stop_bits: rodbus::StopBits::One,
-            parity: rodbus::Parity::None,
-            flow_control: rodbus::FlowControl::None,
-        };
-
-        let mut channel = client::spawn_rtu_client_task(
-            &current_settings.usb_port,
-            settings,
-            1,
-            default_retry_strategy(),
-            DecodeLevel::default(),
-            None,
-        );
-
-        if let Err(err) = channel.enable().await {
-            return Err(format!("Failed to enable connection {:?}", err));
-        }
-
-        let params = RequestParam::new(UnitId::new(10), std::time::Duration::from_secs(1));
-        for attempt in 1..=3 {
-            match channel
-                .read_coils(params, AddressRange::try_from(1, 1).unwrap())
-                .await
-            {
-                Ok(_) => {
-                    current_connection.set_connection(channel);
-                    let mut ds = data_source_state.lock().await;
-                    *ds = DataSource::Live;
-                    return Ok("Connected successfully".into());
-                }
-                Err(err) => {
-                    println!("Attempt {}/3 failed: {:?}", attempt, err);
-                    if attempt < 3 {
-                        tokio::time::sleep(std::time::Duration::from_secs(1)).await
-                    }
-                }
-            }
-        }
+    let Some(current_settings) = &current_settings.settings else {
+        return Err(AppError::no_data("No settings found"));
+    };
+    if current_connection.is_connected() {
+        return Err(AppError::AlreadyConnected);
     }
 
-    Err("Failed to connect after 3 attempts".into())
+    let client = ModbusClient::connect(current_settings).await?;
+    current_connection.set_connection(client);
+    emit_connection_status(&app_handle, &current_connection.health);
+    let mut ds = data_source_state.lock().await;
+    *ds = DataSource::Live;
+    Ok(format!(
+        "Connected to {}",
+        ModbusClient::describe(current_settings)
+    ))
 }
 
 #[tauri::command]
 pub async fn is_connected(
     connection: State<'_, Mutex<CurrentConnection>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let current_connection = connection.lock().await;
     if current_connection.is_connected() {
         Ok("Connected".into())
     } else {
-        Err("No active connection".into())
+        Err(AppError::NotConnected)
     }
 }
 
 #[tauri::command]
 pub async fn disconnect_modbus(
+    app_handle: AppHandle,
     connection: State<'_, Mutex<CurrentConnection>>,
-    transmission_state: State<'_, Mutex<TransmissionState>>,
-    data_source_state: State<'_, Mutex<DataSource>>,
-) -> Result<String, String> {
+    acquisition_state: State<'_, Mutex<Acquisition>>,
+    run_log_state: State<'_, Mutex<RunLog>>,
+) -> Result<String, AppError> {
     let mut current_connection = connection.lock().await;
 
     if current_connection.is_connected() {
         current_connection.clear_connection();
-        let _ = cancel_column_data(transmission_state, data_source_state);
+        emit_connection_status(&app_handle, &current_connection.health);
+        emitter::stop(&app_handle, &acquisition_state).await;
+        run_log_state.lock().await.finish();
         Ok("Disconnected succesfully".into())
     } else {
-        Err("No connection to disconnect".into())
+        Err(AppError::NotConnected)
     }
 }
 
@@ -130,129 +466,186 @@ pub async fn read_holding_registers(
     count: u16,
     timeout: u64,
     unit_id: u8,
-) -> Result<Vec<RegisterResponse>, String> {
-    let channel = {
-        let current_connection = connection_state.lock().await;
-        current_connection.connection.clone()
-    };
+) -> Result<Vec<RegisterResponse>, AppError> {
+    let channel = active_client(&connection_state).await?;
+    let address_range = address_range(address, count)?;
 
-    let Some(channel) = channel else {
-        return Err("No active connection".into());
-    };
+    let mut cnx = channel.lock().await;
+    let response = cnx
+        .read_holding_registers(request_param(unit_id, timeout), address_range)
+        .await
+        .map_err(|e| AppError::modbus(format!("reading holding registers at {}", address), e))?;
 
-    let params = RequestParam::new(
-        UnitId::new(unit_id),
-        std::time::Duration::from_secs(timeout),
-    );
-    let address_range = AddressRange::try_from(address, count)
-        .map_err(|e| format!("Invalid address range: {:?}", e))?;
+    Ok(response.into_iter().map(RegisterResponse::from).collect())
+}
+
+#[tauri::command]
+pub async fn read_input_registers(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    address: u16,
+    count: u16,
+    timeout: u64,
+    unit_id: u8,
+) -> Result<Vec<RegisterResponse>, AppError> {
+    let channel = active_client(&connection_state).await?;
+    let address_range = address_range(address, count)?;
 
     let mut cnx = channel.lock().await;
+    let response = cnx
+        .read_input_registers(request_param(unit_id, timeout), address_range)
+        .await
+        .map_err(|e| AppError::modbus(format!("reading input registers at {}", address), e))?;
+
+    Ok(response.into_iter().map(RegisterResponse::from).collect())
+}
 
+#[tauri::command]
+pub async fn read_coils(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    address: u16,
+    count: u16,
+    timeout: u64,
+    unit_id: u8,
+) -> Result<Vec<CoilResponse>, AppError> {
+    let channel = active_client(&connection_state).await?;
+    let address_range = address_range(address, count)?;
+
+    let mut cnx = channel.lock().await;
     let response = cnx
-        .read_holding_registers(params, address_range)
+        .read_coils(request_param(unit_id, timeout), address_range)
         .await
-        .map_err(|e| format!("Error reading Modbus device: {:?}", e))?;
+        .map_err(|e| AppError::modbus(format!("reading coils at {}", address), e))?;
 
-    let registers: Vec<RegisterResponse> = response
-        .iter()
-        .map(|r| RegisterResponse {
-            index: r.index,
-            value: r.value,
-        })
-        .collect();
+    Ok(response.into_iter().map(CoilResponse::from).collect())
+}
+
+#[tauri::command]
+pub async fn read_discrete_inputs(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    address: u16,
+    count: u16,
+    timeout: u64,
+    unit_id: u8,
+) -> Result<Vec<CoilResponse>, AppError> {
+    let channel = active_client(&connection_state).await?;
+    let address_range = address_range(address, count)?;
 
-    Ok(registers)
+    let mut cnx = channel.lock().await;
+    let response = cnx
+        .read_discrete_inputs(request_param(unit_id, timeout), address_range)
+        .await
+        .map_err(|e| AppError::modbus(format!("reading discrete inputs at {}", address), e))?;
+
+    Ok(response.into_iter().map(CoilResponse::from).collect())
 }
 
 #[tauri::command]
 pub async fn write_single_register(
-    connection: State<'_, Mutex<CurrentConnection>>,
+    connection_state: State<'_, Mutex<CurrentConnection>>,
     value: u16,
     address: u16,
     timeout: u64,
     unit_id: u8,
-) -> Result<String, String> {
-    let current_connection = connection.lock().await;
+) -> Result<RegisterResponse, AppError> {
+    let channel = active_client(&connection_state).await?;
 
-    if let Some(channel) = &current_connection.connection {
-        let params = RequestParam::new(
-            UnitId::new(unit_id),
-            std::time::Duration::from_secs(timeout),
-        );
-        let mut cnx = channel.lock().await;
-        match cnx
-            .write_single_register(params, Indexed::new(address, value))
-            .await
-        {
-            Ok(response) => Ok(format!("{:?}", response)),
-            Err(err) => Err(format!("Write error: {:?}", err)),
-        }
-    } else {
-        Err("No active connection".into())
-    }
+    let mut cnx = channel.lock().await;
+    let response = cnx
+        .write_single_register(
+            request_param(unit_id, timeout),
+            Indexed::new(address, value),
+        )
+        .await
+        .map_err(|e| AppError::modbus(format!("writing register at {}", address), e))?;
+
+    Ok(RegisterResponse::from(response))
 }
 
 #[tauri::command]
 pub async fn write_single_coil(
-    connection: State<'_, Mutex<CurrentConnection>>,
+    connection_state: State<'_, Mutex<CurrentConnection>>,
     value: bool,
     address: u16,
     timeout: u64,
     unit_id: u8,
-) -> Result<String, String> {
-    let current_connection = connection.lock().await;
+) -> Result<CoilResponse, AppError> {
+    let channel = active_client(&connection_state).await?;
 
-    if let Some(channel) = &current_connection.connection {
-        let params = RequestParam::new(
-            UnitId::new(unit_id),
-            std::time::Duration::from_secs(timeout),
-        );
+    let mut cnx = channel.lock().await;
+    let response = cnx
+        .write_single_coil(
+            request_param(unit_id, timeout),
+            Indexed::new(address, value),
+        )
+        .await
+        .map_err(|e| AppError::modbus(format!("writing coil at {}", address), e))?;
 
-        let mut cnx = channel.lock().await;
-        match cnx
-            .write_single_coil(params, Indexed::new(address, value))
-            .await
-        {
-            Ok(response) => Ok(format!("{:?}", response)),
-            Err(err) => Err(format!("Write error: {:?}", err)),
-        }
-    } else {
-        Err("No active connection".into())
-    }
+    Ok(CoilResponse::from(response))
 }
 
 #[tauri::command]
-pub async fn read_coils(
-    connection: State<'_, Mutex<CurrentConnection>>,
+pub async fn write_multiple_registers(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    values: Vec<u16>,
     address: u16,
-    count: u16,
     timeout: u64,
     unit_id: u8,
-) -> Result<String, String> {
-    let current_connection = connection.lock().await;
+) -> Result<WriteMultipleResponse, AppError> {
+    let channel = active_client(&connection_state).await?;
 
-    if let Some(channel) = &current_connection.connection {
-        let params = RequestParam::new(
-            UnitId::new(unit_id),
-            std::time::Duration::from_secs(timeout),
-        );
+    let mut cnx = channel.lock().await;
+    let range = cnx
+        .write_multiple_registers(request_param(unit_id, timeout), address, values)
+        .await
+        .map_err(|e| AppError::modbus(format!("writing registers at {}", address), e))?;
 
-        let mut cnx = channel.lock().await;
-        match cnx
-            .read_coils(params, AddressRange::try_from(address, count).unwrap())
-            .await
-        {
-            Ok(response) => Ok(format!("{:?}", response)),
-            Err(err) => Err(format!("Read error: {:?}", err)),
-        }
-    } else {
-        Err("No active connection".into())
-    }
+    Ok(WriteMultipleResponse::from(range))
 }
 
 #[tauri::command]
-pub async fn available_ports() -> Result<Vec<String>, String> {
+pub async fn write_multiple_coils(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+    values: Vec<bool>,
+    address: u16,
+    timeout: u64,
+    unit_id: u8,
+) -> Result<WriteMultipleResponse, AppError> {
+    let channel = active_client(&connection_state).await?;
+
+    let mut cnx = channel.lock().await;
+    let range = cnx
+        .write_multiple_coils(request_param(unit_id, timeout), address, values)
+        .await
+        .map_err(|e| AppError::modbus(format!("writing coils at {}", address), e))?;
+
+    Ok(WriteMultipleResponse::from(range))
+}
+
+async fn active_client(
+    connection_state: &State<'_, Mutex<CurrentConnection>>,
+) -> Result<Arc<Mutex<ModbusClient>>, AppError> {
+    let current_connection = connection_state.lock().await;
+    current_connection
+        .connection
+        .clone()
+        .ok_or(AppError::NotConnected)
+}
+
+fn address_range(address: u16, count: u16) -> Result<AddressRange, AppError> {
+    AddressRange::try_from(address, count)
+        .map_err(|e| AppError::modbus(format!("addressing {}", address), e.into()))
+}
+
+/// Request to `unit_id` waiting `timeout` milliseconds for the answer
+fn request_param(unit_id: u8, timeout: u64) -> RequestParam {
+    RequestParam::new(
+        UnitId::new(unit_id),
+        std::time::Duration::from_millis(timeout),
+    )
+}
+
+/// Names of the USB serial adapters present
+pub fn usb_ports() -> Result<Vec<String>, AppError> {
     match serialport::available_ports() {
         Ok(ports) => {
             let port_names: Vec<String> = ports
@@ -270,6 +663,89 @@ pub async fn available_ports() -> Result<Vec<String>, String> {
                 .collect();
             Ok(port_names)
         }
-        Err(err) => Err(format!("Failed to list serial ports: {}", err)),
+        Err(err) => Err(AppError::internal(format!(
+            "Failed to list serial ports: {}",
+            err
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn available_ports() -> Result<Vec<String>, AppError> {
+    usb_ports()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodbus::server::{spawn_tcp_server_task, AddressFilter, RequestHandler, ServerHandlerMap};
+    use std::net::SocketAddr;
+
+    struct Registers([u16; 8]);
+
+    impl RequestHandler for Registers {
+        fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+            address
+                .checked_sub(100)
+                .and_then(|i| self.0.get(i as usize).copied())
+                .ok_or(ExceptionCode::IllegalDataAddress)
+        }
+
+        fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+            let register = value
+                .index
+                .checked_sub(100)
+                .and_then(|i| self.0.get_mut(i as usize))
+                .ok_or(ExceptionCode::IllegalDataAddress)?;
+            *register = value.value;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn tcp_transport_round_trip() {
+        // free port for the server, released before it binds
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let registers = Registers([250, 780, 0, 0, 0, 0, 0, 0]);
+        let _server = spawn_tcp_server_task(
+            1,
+            SocketAddr::from(([127, 0, 0, 1], port)),
+            ServerHandlerMap::single(UnitId::new(10), registers.wrap()),
+            AddressFilter::Any,
+            DecodeLevel::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut settings = Settings {
+            transport: Transport::Tcp,
+            ..Settings::default()
+        };
+        settings.network.port = port;
+        let mut client = ModbusClient::connect(&settings).await.unwrap();
+
+        let param = request_param(settings.unit_id, settings.timeout);
+        let range = AddressRange::try_from(100, 2).unwrap();
+        let read = client.read_holding_registers(param, range).await.unwrap();
+        assert_eq!(
+            read.iter().map(|r| (r.index, r.value)).collect::<Vec<_>>(),
+            [(100, 250), (101, 780)]
+        );
+
+        let written = client
+            .write_single_register(param, Indexed::new(101, 795))
+            .await
+            .unwrap();
+        assert_eq!((written.index, written.value), (101, 795));
+        let read = client.read_holding_registers(param, range).await.unwrap();
+        assert_eq!(read[1].value, 795);
+
+        // the server only answers unit 10
+        let other = request_param(11, 200);
+        assert!(client.read_holding_registers(other, range).await.is_err());
     }
 }
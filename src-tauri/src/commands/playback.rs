@@ -0,0 +1,481 @@
+use super::data_manager::{ColumnEntry, DataSource};
+use super::emitter::emit_column_data;
+use super::error::AppError;
+use super::history::MeasurementHistory;
+use super::settings::SettingsState;
+use super::thermodynamics::BinaryMixture;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 100.0;
+/// Shortest wait between entries, keeps fast playback from flooding the frontend
+const MIN_DELAY: Duration = Duration::from_millis(10);
+/// Longest wait between entries with recorded pacing, such as a gap in a run
+const MAX_RECORDED_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaybackPacing {
+    /// One entry per sample period
+    #[default]
+    SamplePeriod,
+    /// The time between the original timestamps
+    Recorded,
+}
+
+/// Compositions computed again while playing, with the mixture in the current
+/// settings instead of the one the entries were recorded with
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Recompute {
+    /// Pressure in mmHg replacing the recorded one
+    pub pressure: Option<f64>,
+}
+
+/// Absolute difference between the recomputed and recorded compositions of a plate
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlateDeviation {
+    pub plate: usize,
+    pub mean: f64,
+    pub max: f64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecomputeReport {
+    pub entries: usize,
+    /// Entries with temperatures outside the boiling range, left without compositions
+    pub out_of_range: usize,
+    pub solver_failures: usize,
+    pub plates: Vec<PlateDeviation>,
+    /// The recomputed compositions replaced the recorded ones
+    pub applied: bool,
+}
+
+/// Imported entries being played back. `index` is the next entry to play, so
+/// the entry shown is the one before it.
+#[derive(Clone)]
+pub struct Playback {
+    data: Vec<Arc<ColumnEntry>>,
+    index: usize,
+    speed: f64,
+    pacing: PlaybackPacing,
+    looping: bool,
+    recompute: Option<Recompute>,
+}
+
+/// Position of the playback, emitted to the frontend as `playback_position`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybackPosition {
+    /// Entries played
+    pub index: usize,
+    pub total: usize,
+    /// Timestamp of the entry shown
+    pub timestamp: Option<u64>,
+    pub percentage_complete: f64,
+    pub speed: f64,
+    pub pacing: PlaybackPacing,
+    pub looping: bool,
+    pub recompute: Option<Recompute>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Playback {
+    pub fn new(entries: Vec<ColumnEntry>) -> Self {
+        Self {
+            data: entries.into_iter().map(Arc::new).collect(),
+            index: 0,
+            speed: 1.0,
+            pacing: PlaybackPacing::default(),
+            looping: false,
+            recompute: None,
+        }
+    }
+
+    pub fn entries(&self) -> &[Arc<ColumnEntry>] {
+        &self.data
+    }
+
+    pub fn recompute(&self) -> Option<Recompute> {
+        self.recompute
+    }
+
+    /// Plays the next entry, starting over at the end when looping
+    pub fn next(&mut self) -> Option<Arc<ColumnEntry>> {
+        if self.index >= self.data.len() && self.looping {
+            self.index = 0;
+        }
+        let entry = self.entry(self.index)?;
+        self.index += 1;
+        Some(entry)
+    }
+
+    /// Moves the entry shown by `steps`, backwards when negative, and returns it
+    pub fn step(&mut self, steps: i64) -> Option<Arc<ColumnEntry>> {
+        let last = self.data.len().checked_sub(1)?;
+        let shown = (self.index as i64 - 1 + steps).clamp(0, last as i64) as usize;
+        self.index = shown + 1;
+        self.entry(shown)
+    }
+
+    /// Makes `index` the next entry to play
+    pub fn seek(&mut self, index: usize) {
+        self.index = index.min(self.data.len());
+    }
+
+    /// Index of the first entry at or after `timestamp`
+    pub fn index_at(&self, timestamp: u64) -> usize {
+        self.data
+            .partition_point(|entry| entry.timestamp < timestamp)
+    }
+
+    /// Wait before the next entry is played
+    pub fn delay(&self, sample_period: Duration) -> Duration {
+        let delay = match self.pacing {
+            PlaybackPacing::SamplePeriod => sample_period.div_f64(self.speed),
+            PlaybackPacing::Recorded => {
+                let gap = match (self.index.checked_sub(1), self.data.get(self.index)) {
+                    (Some(shown), Some(next)) => {
+                        next.timestamp.saturating_sub(self.data[shown].timestamp)
+                    }
+                    _ => 0,
+                };
+                Duration::from_millis(gap)
+                    .div_f64(self.speed)
+                    .min(MAX_RECORDED_DELAY)
+            }
+        };
+        delay.max(MIN_DELAY)
+    }
+
+    pub fn position(&self) -> PlaybackPosition {
+        let total = self.data.len();
+        PlaybackPosition {
+            index: self.index,
+            total,
+            timestamp: self
+                .index
+                .checked_sub(1)
+                .and_then(|shown| self.data.get(shown))
+                .map(|entry| entry.timestamp),
+            percentage_complete: percentage(self.index, total),
+            speed: self.speed,
+            pacing: self.pacing,
+            looping: self.looping,
+            recompute: self.recompute,
+        }
+    }
+
+    /// Entry at `index` with its playback progress
+    fn entry(&self, index: usize) -> Option<Arc<ColumnEntry>> {
+        let entry = self.data.get(index)?;
+        Some(Arc::new(ColumnEntry {
+            percentage_complete: percentage(index + 1, self.data.len()),
+            ..ColumnEntry::clone(entry)
+        }))
+    }
+}
+
+/// Recomputes every entry, each warm started from the one before, and compares
+/// the result with the recorded compositions
+fn recompute_entries(
+    entries: &[Arc<ColumnEntry>],
+    mixture: &BinaryMixture,
+    pressure: Option<f64>,
+) -> (Vec<ColumnEntry>, RecomputeReport) {
+    let mut recomputed: Vec<ColumnEntry> = Vec::with_capacity(entries.len());
+    let mut report = RecomputeReport {
+        entries: entries.len(),
+        out_of_range: 0,
+        solver_failures: 0,
+        plates: Vec::new(),
+        applied: false,
+    };
+    let mut compared: Vec<usize> = Vec::new();
+    for entry in entries {
+        let previous = recomputed.last().map(|entry| entry.compositions.as_slice());
+        let (new_entry, failures) = entry.recompute(mixture, pressure, previous);
+        report.solver_failures += failures;
+        if new_entry.compositions.is_empty() && !entry.temperatures.is_empty() {
+            report.out_of_range += 1;
+        }
+
+        for (plate, (new, old)) in new_entry
+            .compositions
+            .iter()
+            .zip(&entry.compositions)
+            .enumerate()
+        {
+            if report.plates.len() <= plate {
+                report.plates.push(PlateDeviation {
+                    plate,
+                    mean: 0.0,
+                    max: 0.0,
+                });
+                compared.push(0);
+            }
+            let deviation = &mut report.plates[plate];
+            let difference = (new - old).abs();
+            deviation.max = deviation.max.max(difference);
+            deviation.mean += difference;
+            compared[plate] += 1;
+        }
+        recomputed.push(new_entry);
+    }
+    for (deviation, &count) in report.plates.iter_mut().zip(&compared) {
+        deviation.mean /= count.max(1) as f64;
+    }
+    (recomputed, report)
+}
+
+fn validate_pressure(pressure: Option<f64>) -> Result<(), AppError> {
+    match pressure {
+        Some(pressure) if !(pressure.is_finite() && pressure > 0.0) => Err(AppError::settings(
+            "pressure",
+            format!("Pressure {} is not positive", pressure),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn percentage(played: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    played as f64 / total as f64 * 100.0
+}
+
+/// Entry as shown, with its compositions recomputed when enabled. Timed playback
+/// and stepping both go through here so they show the same values.
+pub async fn played_entry(
+    settings_state: &State<'_, Mutex<SettingsState>>,
+    entry: Arc<ColumnEntry>,
+    recompute: Option<Recompute>,
+    previous: Option<&ColumnEntry>,
+) -> Result<Arc<ColumnEntry>, AppError> {
+    let Some(recompute) = recompute else {
+        return Ok(entry);
+    };
+    let mixture = {
+        let current_settings = settings_state.lock().await;
+        current_settings
+            .settings
+            .as_ref()
+            .map(|settings| settings.mixture_model())
+            .ok_or(AppError::no_data("No settings found"))?
+    };
+    let (entry, _) = entry.recompute(
+        &mixture,
+        recompute.pressure,
+        previous.map(|entry| entry.compositions.as_slice()),
+    );
+    Ok(Arc::new(entry))
+}
+
+/// Applies `update` to the playback and emits the new position
+async fn update_playback<T>(
+    app_handle: &AppHandle,
+    data_source_state: &State<'_, Mutex<DataSource>>,
+    update: impl FnOnce(&mut Playback) -> Result<T, AppError>,
+) -> Result<(T, PlaybackPosition), AppError> {
+    let mut ds = data_source_state.lock().await;
+    let DataSource::Playback(playback) = &mut *ds else {
+        return Err(AppError::InvalidState {
+            message: "No playback loaded".into(),
+        });
+    };
+    let result = update(playback)?;
+    let position = playback.position();
+    if let Err(e) = app_handle.emit("playback_position", &position) {
+        eprintln!("Failed to emit playback position: {}", e);
+    }
+    Ok((result, position))
+}
+
+#[tauri::command]
+pub async fn playback_position(
+    data_source_state: State<'_, Mutex<DataSource>>,
+) -> Result<PlaybackPosition, AppError> {
+    match &*data_source_state.lock().await {
+        DataSource::Playback(playback) => Ok(playback.position()),
+        _ => Err(AppError::no_data("No playback loaded")),
+    }
+}
+
+/// Sets the speed multiplier and, when given, how entries are paced
+#[tauri::command]
+pub async fn set_playback_speed(
+    app_handle: AppHandle,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    speed: f64,
+    pacing: Option<PlaybackPacing>,
+) -> Result<PlaybackPosition, AppError> {
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(AppError::settings(
+            "speed",
+            format!("Speed must be between {} and {}", MIN_SPEED, MAX_SPEED),
+        ));
+    }
+    let (_, position) = update_playback(&app_handle, &data_source_state, |playback| {
+        playback.speed = speed;
+        if let Some(pacing) = pacing {
+            playback.pacing = pacing;
+        }
+        Ok(())
+    })
+    .await?;
+    Ok(position)
+}
+
+#[tauri::command]
+pub async fn set_playback_loop(
+    app_handle: AppHandle,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    looping: bool,
+) -> Result<PlaybackPosition, AppError> {
+    let (_, position) = update_playback(&app_handle, &data_source_state, |playback| {
+        playback.looping = looping;
+        Ok(())
+    })
+    .await?;
+    Ok(position)
+}
+
+/// Moves the playback to an entry index or to the first entry at or after a
+/// timestamp in milliseconds, which is played next
+#[tauri::command]
+pub async fn seek_playback(
+    app_handle: AppHandle,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    index: Option<usize>,
+    timestamp: Option<u64>,
+) -> Result<PlaybackPosition, AppError> {
+    let (_, position) = update_playback(&app_handle, &data_source_state, |playback| {
+        let index = match (index, timestamp) {
+            (Some(index), _) => index,
+            (None, Some(timestamp)) => playback.index_at(timestamp),
+            (None, None) => {
+                return Err(AppError::settings(
+                    "index",
+                    "An index or timestamp is required",
+                ))
+            }
+        };
+        playback.seek(index);
+        Ok(())
+    })
+    .await?;
+    Ok(position)
+}
+
+/// Computes the compositions of the entries again while playing, with the
+/// mixture in the current settings and optionally another pressure
+#[tauri::command]
+pub async fn set_playback_recompute(
+    app_handle: AppHandle,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    enabled: bool,
+    pressure: Option<f64>,
+) -> Result<PlaybackPosition, AppError> {
+    validate_pressure(pressure)?;
+    let (_, position) = update_playback(&app_handle, &data_source_state, |playback| {
+        playback.recompute = enabled.then_some(Recompute { pressure });
+        Ok(())
+    })
+    .await?;
+    Ok(position)
+}
+
+/// Recomputes the compositions of the whole playback with the mixture in the
+/// current settings, at `pressure` or the recorded pressure, and compares them
+/// with the recorded ones. With `apply` the recomputed entries replace the
+/// recorded ones, so the corrected run can be played and exported.
+#[tauri::command]
+pub async fn recompute_compositions(
+    settings_state: State<'_, Mutex<SettingsState>>,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    pressure: Option<f64>,
+    apply: Option<bool>,
+) -> Result<RecomputeReport, AppError> {
+    validate_pressure(pressure)?;
+    let mixture = {
+        let current_settings = settings_state.lock().await;
+        current_settings
+            .settings
+            .as_ref()
+            .map(|settings| settings.mixture_model())
+            .ok_or(AppError::no_data("No settings found"))?
+    };
+    // solved without the lock so a running playback is not held up
+    let entries = match &*data_source_state.lock().await {
+        DataSource::Playback(playback) if !playback.data.is_empty() => playback.data.clone(),
+        _ => return Err(AppError::no_data("No playback loaded")),
+    };
+
+    let (recomputed, mut report) =
+        tokio::task::spawn_blocking(move || recompute_entries(&entries, &mixture, pressure))
+            .await
+            .map_err(AppError::internal)?;
+    println!(
+        "Recomputed {} entries, {} out of range, {} solver failures",
+        report.entries, report.out_of_range, report.solver_failures
+    );
+
+    if apply.unwrap_or_default() {
+        let mut ds = data_source_state.lock().await;
+        if let DataSource::Playback(playback) = &mut *ds {
+            if playback.data.len() == recomputed.len() {
+                playback.data = recomputed.into_iter().map(Arc::new).collect();
+                report.applied = true;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Shows the entry `steps` away from the one shown, backwards when negative
+#[tauri::command]
+pub async fn step_playback(
+    app_handle: AppHandle,
+    settings_state: State<'_, Mutex<SettingsState>>,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    history_state: State<'_, Mutex<MeasurementHistory>>,
+    steps: i64,
+) -> Result<PlaybackPosition, AppError> {
+    let ((entry, recompute), position) =
+        update_playback(&app_handle, &data_source_state, |playback| {
+            let entry = playback
+                .step(steps)
+                .ok_or(AppError::no_data("No playback data"))?;
+            Ok((entry, playback.recompute()))
+        })
+        .await?;
+    let previous = history_state.lock().await.last();
+    let entry = played_entry(&settings_state, entry, recompute, previous.as_deref()).await?;
+
+    let history_settings = {
+        let current_settings = settings_state.lock().await;
+        current_settings
+            .settings
+            .as_ref()
+            .map(|settings| settings.history.clone())
+            .unwrap_or_default()
+    };
+    history_state
+        .lock()
+        .await
+        .push_played(entry.clone(), &history_settings);
+    emit_column_data(&app_handle, entry).await?;
+    Ok(position)
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RegisterDataType {
+    #[default]
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+}
+
+impl RegisterDataType {
+    pub fn register_count(&self) -> u16 {
+        match self {
+            RegisterDataType::U16 | RegisterDataType::I16 => 1,
+            RegisterDataType::U32 | RegisterDataType::I32 | RegisterDataType::F32 => 2,
+        }
+    }
+}
+
+/// Order of the value bytes on the wire, A being the most significant byte.
+/// 16 bit values only use the first word, so `Cdab` behaves as `Abcd` and `Dcba` as `Badc`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ByteOrder {
+    #[default]
+    Abcd,
+    Cdab,
+    Badc,
+    Dcba,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegisterFormat {
+    pub data_type: RegisterDataType,
+    pub byte_order: ByteOrder,
+}
+
+impl RegisterFormat {
+    /// Raw value of the registers, `None` when fewer registers than the data type needs are given
+    pub fn decode(&self, registers: &[u16]) -> Option<f64> {
+        let count = self.data_type.register_count() as usize;
+        if registers.len() < count {
+            return None;
+        }
+
+        let wire: Vec<u8> = registers[..count]
+            .iter()
+            .flat_map(|register| register.to_be_bytes())
+            .collect();
+        let bytes: Vec<u8> = match self.byte_order {
+            ByteOrder::Abcd => wire,
+            ByteOrder::Dcba => wire.into_iter().rev().collect(),
+            ByteOrder::Badc => wire.chunks(2).flat_map(|word| [word[1], word[0]]).collect(),
+            ByteOrder::Cdab => wire.chunks(2).rev().flatten().copied().collect(),
+        };
+
+        let value = match self.data_type {
+            RegisterDataType::U16 => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            RegisterDataType::I16 => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            RegisterDataType::U32 => {
+                u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+            RegisterDataType::I32 => {
+                i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+            RegisterDataType::F32 => {
+                f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+        };
+        Some(value)
+    }
+
+    /// Registers holding `value`, rounded and saturated to the data type
+    pub fn encode(&self, value: f64) -> Vec<u16> {
+        let bytes: Vec<u8> = match self.data_type {
+            RegisterDataType::U16 => (value.round() as u16).to_be_bytes().to_vec(),
+            RegisterDataType::I16 => (value.round() as i16).to_be_bytes().to_vec(),
+            RegisterDataType::U32 => (value.round() as u32).to_be_bytes().to_vec(),
+            RegisterDataType::I32 => (value.round() as i32).to_be_bytes().to_vec(),
+            RegisterDataType::F32 => (value as f32).to_be_bytes().to_vec(),
+        };
+        // every order is its own inverse
+        let wire: Vec<u8> = match self.byte_order {
+            ByteOrder::Abcd => bytes,
+            ByteOrder::Dcba => bytes.into_iter().rev().collect(),
+            ByteOrder::Badc => bytes
+                .chunks(2)
+                .flat_map(|word| [word[1], word[0]])
+                .collect(),
+            ByteOrder::Cdab => bytes.chunks(2).rev().flatten().copied().collect(),
+        };
+        wire.chunks(2)
+            .map(|word| u16::from_be_bytes([word[0], word[1]]))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    pub fn to_celsius(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value,
+            TemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            TemperatureUnit::Kelvin => value - 273.15,
+        }
+    }
+
+    pub fn celsius_to_unit(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value,
+            TemperatureUnit::Fahrenheit => value * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => value + 273.15,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PressureUnit {
+    #[default]
+    MmHg,
+    KPa,
+    Bar,
+    Psi,
+    Atm,
+}
+
+impl PressureUnit {
+    pub fn mmhg_to_unit(self, value: f64) -> f64 {
+        value / self.to_mmhg(1.0)
+    }
+
+    pub fn to_mmhg(self, value: f64) -> f64 {
+        match self {
+            PressureUnit::MmHg => value,
+            PressureUnit::KPa => value * 760.0 / 101.325,
+            PressureUnit::Bar => value * 760.0 / 1.01325,
+            PressureUnit::Psi => value * 760.0 / 14.695_949,
+            PressureUnit::Atm => value * 760.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(data_type: RegisterDataType, byte_order: ByteOrder) -> RegisterFormat {
+        RegisterFormat {
+            data_type,
+            byte_order,
+        }
+    }
+
+    #[test]
+    fn decodes_every_byte_order() {
+        // 0x12345678 with A = 0x12 the most significant byte
+        let cases = [
+            (ByteOrder::Abcd, [0x1234, 0x5678]),
+            (ByteOrder::Cdab, [0x5678, 0x1234]),
+            (ByteOrder::Badc, [0x3412, 0x7856]),
+            (ByteOrder::Dcba, [0x7856, 0x3412]),
+        ];
+        for (byte_order, registers) in cases {
+            let format = format(RegisterDataType::U32, byte_order);
+            assert_eq!(
+                format.decode(&registers),
+                Some(0x12345678 as f64),
+                "{:?}",
+                byte_order
+            );
+            assert_eq!(
+                format.encode(0x12345678 as f64),
+                registers,
+                "{:?}",
+                byte_order
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_every_type_and_order() {
+        let values = [
+            (RegisterDataType::U16, 54321.0),
+            (RegisterDataType::I16, -1234.0),
+            (RegisterDataType::U32, 3_000_000_000.0),
+            (RegisterDataType::I32, -2_000_000_000.0),
+            (RegisterDataType::F32, 78.375),
+        ];
+        let orders = [
+            ByteOrder::Abcd,
+            ByteOrder::Cdab,
+            ByteOrder::Badc,
+            ByteOrder::Dcba,
+        ];
+        for (data_type, value) in values {
+            for byte_order in orders {
+                let format = format(data_type, byte_order);
+                let registers = format.encode(value);
+                assert_eq!(registers.len(), data_type.register_count() as usize);
+                assert_eq!(format.decode(&registers), Some(value), "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn single_word_orders_only_swap_bytes() {
+        let swapped = [0x3412];
+        for byte_order in [ByteOrder::Badc, ByteOrder::Dcba] {
+            let format = format(RegisterDataType::U16, byte_order);
+            assert_eq!(format.decode(&swapped), Some(0x1234 as f64));
+        }
+        let format = format(RegisterDataType::U16, ByteOrder::Cdab);
+        assert_eq!(format.decode(&[0x1234]), Some(0x1234 as f64));
+    }
+
+    #[test]
+    fn decode_needs_every_register() {
+        let format = format(RegisterDataType::F32, ByteOrder::Abcd);
+        assert_eq!(format.decode(&[0x4296]), None);
+    }
+}
@@ -0,0 +1,401 @@
+use rodbus::client::RequestParam;
+use rodbus::{
+    AddressRange, AduParseError, ExceptionCode, FrameParseError, Indexed, InvalidRequest,
+    RequestError,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const READ_COILS: u8 = 0x01;
+const READ_DISCRETE_INPUTS: u8 = 0x02;
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+const READ_INPUT_REGISTERS: u8 = 0x04;
+const WRITE_SINGLE_COIL: u8 = 0x05;
+const WRITE_SINGLE_REGISTER: u8 = 0x06;
+const WRITE_MULTIPLE_COILS: u8 = 0x0F;
+const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+/// Largest block writes that fit in an RTU frame
+const MAX_WRITE_COILS: u16 = 0x07B0;
+const MAX_WRITE_REGISTERS: u16 = 0x007B;
+
+const COIL_ON: u16 = 0xFF00;
+const COIL_OFF: u16 = 0x0000;
+
+/// Modbus RTU frames carried over a raw TCP socket, as used by serial to Ethernet
+/// gateways working in transparent mode. rodbus only frames TCP with MBAP headers,
+/// so the RTU framing and CRC are done here. The socket is opened on the first
+/// request and dropped after any error so the next request starts on a clean stream.
+pub struct RtuOverTcpClient {
+    host: String,
+    port: u16,
+    stream: Option<TcpStream>,
+}
+
+impl RtuOverTcpClient {
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            stream: None,
+        }
+    }
+
+    pub async fn read_coils(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        let data = self.request(param, READ_COILS, &range_body(range)).await?;
+        unpack_bits(range, &data)
+    }
+
+    pub async fn read_discrete_inputs(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        let data = self
+            .request(param, READ_DISCRETE_INPUTS, &range_body(range))
+            .await?;
+        unpack_bits(range, &data)
+    }
+
+    pub async fn read_holding_registers(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        let data = self
+            .request(param, READ_HOLDING_REGISTERS, &range_body(range))
+            .await?;
+        unpack_registers(range, &data)
+    }
+
+    pub async fn read_input_registers(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        let data = self
+            .request(param, READ_INPUT_REGISTERS, &range_body(range))
+            .await?;
+        unpack_registers(range, &data)
+    }
+
+    pub async fn write_single_coil(
+        &mut self,
+        param: RequestParam,
+        request: Indexed<bool>,
+    ) -> Result<Indexed<bool>, RequestError> {
+        let value = if request.value { COIL_ON } else { COIL_OFF };
+        let echo = self
+            .request(param, WRITE_SINGLE_COIL, &echo_body(request.index, value))
+            .await?;
+        match parse_echo(&echo)? {
+            (index, COIL_ON) if index == request.index => Ok(Indexed::new(index, true)),
+            (index, COIL_OFF) if index == request.index => Ok(Indexed::new(index, false)),
+            (index, _) if index != request.index => Err(AduParseError::ReplyEchoMismatch.into()),
+            (_, value) => Err(AduParseError::UnknownCoilState(value).into()),
+        }
+    }
+
+    pub async fn write_single_register(
+        &mut self,
+        param: RequestParam,
+        request: Indexed<u16>,
+    ) -> Result<Indexed<u16>, RequestError> {
+        let echo = self
+            .request(
+                param,
+                WRITE_SINGLE_REGISTER,
+                &echo_body(request.index, request.value),
+            )
+            .await?;
+        let (index, value) = parse_echo(&echo)?;
+        if index != request.index || value != request.value {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+        Ok(Indexed::new(index, value))
+    }
+
+    pub async fn write_multiple_coils(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        values: &[bool],
+    ) -> Result<AddressRange, RequestError> {
+        let range = write_range(start, values.len(), MAX_WRITE_COILS)?;
+        let mut packed = vec![0u8; values.len().div_ceil(8)];
+        for (i, _) in values.iter().enumerate().filter(|(_, value)| **value) {
+            packed[i / 8] |= 1 << (i % 8);
+        }
+
+        let mut body = range_body(range).to_vec();
+        body.push(packed.len() as u8);
+        body.extend_from_slice(&packed);
+        let echo = self.request(param, WRITE_MULTIPLE_COILS, &body).await?;
+        check_range_echo(range, &echo)
+    }
+
+    pub async fn write_multiple_registers(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        values: &[u16],
+    ) -> Result<AddressRange, RequestError> {
+        let range = write_range(start, values.len(), MAX_WRITE_REGISTERS)?;
+
+        let mut body = range_body(range).to_vec();
+        body.push((values.len() * 2) as u8);
+        body.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        let echo = self.request(param, WRITE_MULTIPLE_REGISTERS, &body).await?;
+        check_range_echo(range, &echo)
+    }
+
+    /// Sends one request and returns the response data without unit, function code and CRC
+    async fn request(
+        &mut self,
+        param: RequestParam,
+        function: u8,
+        body: &[u8],
+    ) -> Result<Vec<u8>, RequestError> {
+        let result = tokio::time::timeout(
+            param.response_timeout,
+            self.exchange(param.id.value, function, body),
+        )
+        .await
+        .unwrap_or(Err(RequestError::ResponseTimeout));
+
+        if result.is_err() {
+            self.stream = None;
+        }
+        result
+    }
+
+    async fn exchange(
+        &mut self,
+        unit_id: u8,
+        function: u8,
+        body: &[u8],
+    ) -> Result<Vec<u8>, RequestError> {
+        if self.stream.is_none() {
+            let stream = TcpStream::connect((self.host.as_str(), self.port))
+                .await
+                .map_err(|_| RequestError::NoConnection)?;
+            stream.set_nodelay(true)?;
+            self.stream = Some(stream);
+        }
+        let Some(stream) = self.stream.as_mut() else {
+            return Err(RequestError::NoConnection);
+        };
+
+        let mut frame = Vec::with_capacity(body.len() + 4);
+        frame.push(unit_id);
+        frame.push(function);
+        frame.extend_from_slice(body);
+        frame.extend_from_slice(&crc16(&frame).to_le_bytes());
+        stream.write_all(&frame).await?;
+
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).await?;
+        let [unit, code] = header;
+
+        let mut response = header.to_vec();
+        let remaining = if code == function | 0x80 {
+            // exception code
+            1
+        } else if code != function {
+            return Err(
+                AduParseError::UnknownResponseFunction(code, function, function | 0x80).into(),
+            );
+        } else if matches!(
+            function,
+            WRITE_SINGLE_COIL
+                | WRITE_SINGLE_REGISTER
+                | WRITE_MULTIPLE_COILS
+                | WRITE_MULTIPLE_REGISTERS
+        ) {
+            // echo of address and value, or of the written range
+            4
+        } else {
+            // byte count followed by the data
+            let count = stream.read_u8().await?;
+            response.push(count);
+            count as usize
+        };
+        let start = response.len();
+        response.resize(start + remaining + 2, 0);
+        stream.read_exact(&mut response[start..]).await?;
+
+        let (content, crc) = response.split_at(response.len() - 2);
+        let received = u16::from_le_bytes([crc[0], crc[1]]);
+        let expected = crc16(content);
+        if received != expected {
+            return Err(FrameParseError::CrcValidationFailure(received, expected).into());
+        }
+        if unit != unit_id {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+        if code != function {
+            return Err(ExceptionCode::from(content[2]).into());
+        }
+
+        Ok(content[2..].to_vec())
+    }
+}
+
+fn range_body(range: AddressRange) -> [u8; 4] {
+    echo_body(range.start, range.count)
+}
+
+fn echo_body(first: u16, second: u16) -> [u8; 4] {
+    let [a, b] = first.to_be_bytes();
+    let [c, d] = second.to_be_bytes();
+    [a, b, c, d]
+}
+
+fn write_range(start: u16, len: usize, max: u16) -> Result<AddressRange, RequestError> {
+    let count = u16::try_from(len).map_err(|_| InvalidRequest::CountTooBigForU16(len))?;
+    if count > max {
+        return Err(InvalidRequest::CountTooBigForType(count, max).into());
+    }
+    Ok(AddressRange::try_from(start, count)?)
+}
+
+fn check_range_echo(range: AddressRange, echo: &[u8]) -> Result<AddressRange, RequestError> {
+    if parse_echo(echo)? != (range.start, range.count) {
+        return Err(AduParseError::ReplyEchoMismatch.into());
+    }
+    Ok(range)
+}
+
+fn parse_echo(data: &[u8]) -> Result<(u16, u16), RequestError> {
+    match data {
+        [a, b, c, d] => Ok((u16::from_be_bytes([*a, *b]), u16::from_be_bytes([*c, *d]))),
+        _ => Err(AduParseError::InsufficientBytes.into()),
+    }
+}
+
+/// Checks the byte count of a read response and returns its data bytes
+fn read_data(data: &[u8], expected: usize) -> Result<&[u8], RequestError> {
+    let (count, bytes) = data.split_first().ok_or(AduParseError::InsufficientBytes)?;
+    if *count as usize != expected || bytes.len() != expected {
+        return Err(
+            AduParseError::InsufficientBytesForByteCount(*count as usize, bytes.len()).into(),
+        );
+    }
+    Ok(bytes)
+}
+
+fn unpack_bits(range: AddressRange, data: &[u8]) -> Result<Vec<Indexed<bool>>, RequestError> {
+    let bytes = read_data(data, (range.count as usize).div_ceil(8))?;
+    Ok((0..range.count)
+        .map(|i| {
+            let bit = bytes[(i / 8) as usize] >> (i % 8) & 1;
+            Indexed::new(range.start + i, bit == 1)
+        })
+        .collect())
+}
+
+fn unpack_registers(range: AddressRange, data: &[u8]) -> Result<Vec<Indexed<u16>>, RequestError> {
+    let bytes = read_data(data, range.count as usize * 2)?;
+    Ok(bytes
+        .chunks(2)
+        .zip(range.start..)
+        .map(|(word, index)| Indexed::new(index, u16::from_be_bytes([word[0], word[1]])))
+        .collect())
+}
+
+/// Modbus RTU CRC, transmitted low byte first
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u16, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodbus::UnitId;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn crc16_matches_known_frame() {
+        // read 10 holding registers from 0 on unit 1, sent as ... C5 CD
+        let frame = [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A];
+        assert_eq!(crc16(&frame).to_le_bytes(), [0xC5, 0xCD]);
+    }
+
+    #[tokio::test]
+    async fn read_holding_registers_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let device = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 8];
+            stream.read_exact(&mut request).await.unwrap();
+
+            let mut response = vec![0x01, READ_HOLDING_REGISTERS, 4, 0x12, 0x34, 0xAB, 0xCD];
+            let crc = crc16(&response);
+            response.extend_from_slice(&crc.to_le_bytes());
+            stream.write_all(&response).await.unwrap();
+            request
+        });
+
+        let mut client = RtuOverTcpClient::new("127.0.0.1", port);
+        let param = RequestParam::new(UnitId::new(1), Duration::from_secs(1));
+        let registers = client
+            .read_holding_registers(param, AddressRange::try_from(100, 2).unwrap())
+            .await
+            .unwrap();
+
+        let request = device.await.unwrap();
+        assert_eq!(
+            request[..6],
+            [0x01, READ_HOLDING_REGISTERS, 0x00, 100, 0x00, 2]
+        );
+        assert_eq!(crc16(&request[..6]).to_le_bytes(), request[6..]);
+        assert_eq!(
+            registers
+                .iter()
+                .map(|register| (register.index, register.value))
+                .collect::<Vec<_>>(),
+            [(100, 0x1234), (101, 0xABCD)]
+        );
+    }
+
+    #[tokio::test]
+    async fn corrupted_crc_is_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 8];
+            stream.read_exact(&mut request).await.unwrap();
+            let mut response = vec![0x01, READ_HOLDING_REGISTERS, 2, 0x00, 0x01];
+            let crc = crc16(&response) ^ 1;
+            response.extend_from_slice(&crc.to_le_bytes());
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let mut client = RtuOverTcpClient::new("127.0.0.1", port);
+        let param = RequestParam::new(UnitId::new(1), Duration::from_secs(1));
+        let result = client
+            .read_holding_registers(param, AddressRange::try_from(0, 1).unwrap())
+            .await;
+        assert!(matches!(
+            result,
+            Err(RequestError::BadFrame(
+                FrameParseError::CrcValidationFailure(..)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,379 @@
+use super::csv_file::CsvOptions;
+use super::data_manager::{ColumnEntry, DataSource};
+use super::error::AppError;
+use super::history::MeasurementHistory;
+use super::playback::Playback;
+use super::settings::Settings;
+use super::utils::{write_history, ImportReport, RejectedRow};
+use super::xlsx_file::ExportMetadata;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
+
+const RUNS_DIR: &str = "runs";
+const RUN_EXTENSION: &str = "jsonl";
+
+/// First line of a run file, the following lines are the entries as JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunHeader {
+    pub id: String,
+    pub started_at: u64,
+    /// Settings the run was acquired with
+    pub settings: Option<Settings>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub id: String,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub samples: usize,
+    pub plate_count: usize,
+    pub mixture: Option<String>,
+    /// The run is still being written
+    pub active: bool,
+}
+
+/// Live entries are appended to a JSON Lines file in the app data dir as they
+/// are acquired, so a run survives a crash. A run starts with the first entry
+/// and ends when acquisition is cancelled or the link is closed.
+#[derive(Default)]
+pub struct RunLog {
+    current: Option<ActiveRun>,
+    /// Id of the last run finished
+    last: Option<String>,
+}
+
+struct ActiveRun {
+    id: String,
+    file: File,
+}
+
+impl RunLog {
+    pub fn append(
+        &mut self,
+        app_handle: &AppHandle,
+        settings: Option<&Settings>,
+        entry: &ColumnEntry,
+    ) -> Result<(), AppError> {
+        let run = match &mut self.current {
+            Some(run) => run,
+            None => self
+                .current
+                .insert(ActiveRun::create(app_handle, settings, entry.timestamp)?),
+        };
+        let line = serde_json::to_string(entry).map_err(|e| AppError::parse("run entry", e))?;
+        // one write per line so a crash leaves at most the last line truncated
+        run.file
+            .write_all(format!("{}\n", line).as_bytes())
+            .map_err(|e| AppError::file(&run.id, e))
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(run) = self.current.take() {
+            println!("Closed run {}", run.id);
+            self.last = Some(run.id);
+        }
+    }
+
+    pub fn active_id(&self) -> Option<&str> {
+        self.current.as_ref().map(|run| run.id.as_str())
+    }
+
+    /// The active run, or else the last one finished
+    pub fn latest_id(&self) -> Option<&str> {
+        self.active_id().or(self.last.as_deref())
+    }
+}
+
+impl ActiveRun {
+    fn create(
+        app_handle: &AppHandle,
+        settings: Option<&Settings>,
+        started_at: u64,
+    ) -> Result<Self, AppError> {
+        let dir = runs_dir(app_handle)?;
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(|e| AppError::file(dir.display().to_string(), e))?;
+        }
+
+        let mut id = format!("run-{}", started_at);
+        let mut path = dir.join(format!("{}.{}", id, RUN_EXTENSION));
+        let mut suffix = 1;
+        while path.exists() {
+            id = format!("run-{}-{}", started_at, suffix);
+            path = dir.join(format!("{}.{}", id, RUN_EXTENSION));
+            suffix += 1;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| AppError::file(path.display().to_string(), e))?;
+
+        let header = RunHeader {
+            id: id.clone(),
+            started_at,
+            settings: settings.cloned(),
+        };
+        let line = serde_json::to_string(&header).map_err(|e| AppError::parse("run header", e))?;
+        file.write_all(format!("{}\n", line).as_bytes())
+            .map_err(|e| AppError::file(path.display().to_string(), e))?;
+        println!("Started run {}", id);
+        Ok(Self { id, file })
+    }
+}
+
+/// Contents of a run file. Lines that cannot be parsed, such as a line cut
+/// short by a crash, are reported with their line number.
+struct RunFile {
+    header: RunHeader,
+    entries: Vec<ColumnEntry>,
+    rejected: Vec<RejectedRow>,
+}
+
+impl RunFile {
+    fn read(path: &Path) -> Result<Self, AppError> {
+        let context = path.display().to_string();
+        let contents = fs::read_to_string(path).map_err(|e| AppError::file(&context, e))?;
+        let mut lines = contents.lines();
+        let header: RunHeader = lines
+            .next()
+            .ok_or(AppError::parse(&context, "Empty run file"))
+            .and_then(|line| {
+                serde_json::from_str(line).map_err(|e| AppError::parse(&context, e))
+            })?;
+
+        let mut entries = Vec::new();
+        let mut rejected = Vec::new();
+        for (index, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ColumnEntry>(line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => rejected.push(RejectedRow {
+                    row: index + 2,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        Ok(Self {
+            header,
+            entries,
+            rejected,
+        })
+    }
+
+    /// Summary of a run without parsing every entry: the header and the last
+    /// entry are parsed and the entries are counted by their line breaks
+    fn summarize(path: &Path, active_id: Option<&str>) -> Result<RunSummary, AppError> {
+        let context = path.display().to_string();
+        let file = File::open(path).map_err(|e| AppError::file(&context, e))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| AppError::file(&context, e))?;
+        if line.trim().is_empty() {
+            return Err(AppError::parse(&context, "Empty run file"));
+        }
+        let header: RunHeader =
+            serde_json::from_str(&line).map_err(|e| AppError::parse(&context, e))?;
+
+        let mut samples = 0;
+        let mut position = line.len() as u64;
+        let mut line_start = position;
+        let mut last_start = None;
+        loop {
+            let buffer = reader.fill_buf().map_err(|e| AppError::file(&context, e))?;
+            if buffer.is_empty() {
+                break;
+            }
+            for (offset, _) in buffer.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+                let end = position + offset as u64;
+                if end > line_start {
+                    samples += 1;
+                    last_start = Some(line_start);
+                }
+                line_start = end + 1;
+            }
+            let read = buffer.len();
+            position += read as u64;
+            reader.consume(read);
+        }
+
+        let last = match last_start {
+            Some(start) => {
+                reader
+                    .seek(SeekFrom::Start(start))
+                    .map_err(|e| AppError::file(&context, e))?;
+                line.clear();
+                reader
+                    .read_line(&mut line)
+                    .map_err(|e| AppError::file(&context, e))?;
+                serde_json::from_str::<ColumnEntry>(&line).ok()
+            }
+            None => None,
+        };
+        let active = active_id == Some(header.id.as_str());
+        Ok(summary(&header, last.as_ref(), samples, active))
+    }
+}
+
+fn summary(
+    header: &RunHeader,
+    last: Option<&ColumnEntry>,
+    samples: usize,
+    active: bool,
+) -> RunSummary {
+    RunSummary {
+        id: header.id.clone(),
+        started_at: header.started_at,
+        ended_at: last.map(|entry| entry.timestamp),
+        samples,
+        plate_count: last
+            .map(|entry| entry.temperatures.len())
+            .unwrap_or_default(),
+        mixture: header
+            .settings
+            .as_ref()
+            .map(|settings| settings.mixture.name()),
+        active,
+    }
+}
+
+fn runs_dir(app_handle: &AppHandle) -> Result<PathBuf, AppError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(RUNS_DIR))
+        .map_err(|e| AppError::file("app data dir", e))
+}
+
+fn run_path(app_handle: &AppHandle, id: &str) -> Result<PathBuf, AppError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::parse(
+            "run id",
+            format!("Invalid run id {:?}", id),
+        ));
+    }
+    let path = runs_dir(app_handle)?.join(format!("{}.{}", id, RUN_EXTENSION));
+    if !path.exists() {
+        return Err(AppError::no_data(format!("Run {} not found", id)));
+    }
+    Ok(path)
+}
+
+/// Lists the logged runs, newest first
+#[tauri::command]
+pub async fn list_runs(
+    app_handle: AppHandle,
+    run_log_state: State<'_, Mutex<RunLog>>,
+) -> Result<Vec<RunSummary>, AppError> {
+    let dir = runs_dir(&app_handle)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let active = {
+        let run_log = run_log_state.lock().await;
+        run_log.active_id().map(String::from)
+    };
+
+    // long runs take a while to scan, keep it off the async workers
+    tokio::task::spawn_blocking(move || {
+        let mut runs = Vec::new();
+        let files = fs::read_dir(&dir).map_err(|e| AppError::file(dir.display().to_string(), e))?;
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(RUN_EXTENSION) {
+                continue;
+            }
+            match RunFile::summarize(&path, active.as_deref()) {
+                Ok(summary) => runs.push(summary),
+                Err(e) => eprintln!("Skipping run file: {}", e),
+            }
+        }
+        runs.sort_by_key(|run| std::cmp::Reverse(run.started_at));
+        Ok(runs)
+    })
+    .await
+    .map_err(AppError::internal)?
+}
+
+/// Loads a logged run as playback data
+#[tauri::command]
+pub async fn open_run(
+    app_handle: AppHandle,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    history_state: State<'_, Mutex<MeasurementHistory>>,
+    id: String,
+) -> Result<ImportReport, AppError> {
+    let run = read_run(&app_handle, &id).await?;
+    let plate_count =
+        summary(&run.header, run.entries.last(), run.entries.len(), false).plate_count;
+    if run.entries.is_empty() {
+        return Err(AppError::no_data(format!("Run {} has no entries", id)));
+    }
+
+    let report = ImportReport {
+        sheet: None,
+        rows_imported: run.entries.len(),
+        rows_rejected: run.rejected,
+        plate_count,
+    };
+    {
+        let mut ds = data_source_state.lock().await;
+        *ds = DataSource::Playback(Playback::new(run.entries));
+    }
+    history_state.lock().await.clear();
+    Ok(report)
+}
+
+/// Every entry logged in run `id`, with the settings it was acquired with
+pub async fn run_entries(
+    app_handle: &AppHandle,
+    id: &str,
+) -> Result<(Vec<Arc<ColumnEntry>>, Option<Settings>), AppError> {
+    let run = read_run(app_handle, id).await?;
+    let entries = run.entries.into_iter().map(Arc::new).collect();
+    Ok((entries, run.header.settings))
+}
+
+/// Parses run `id` on a blocking thread, as `list_runs` scans them
+async fn read_run(app_handle: &AppHandle, id: &str) -> Result<RunFile, AppError> {
+    let path = run_path(app_handle, id)?;
+    tokio::task::spawn_blocking(move || RunFile::read(&path))
+        .await
+        .map_err(AppError::internal)?
+}
+
+/// Exports a logged run as xlsx or CSV, with the settings it was acquired with
+#[tauri::command]
+pub async fn export_run(
+    app_handle: AppHandle,
+    id: String,
+    path: String,
+    csv: Option<CsvOptions>,
+    operator: Option<String>,
+    utc_offset: Option<i32>,
+) -> Result<(), AppError> {
+    let run = read_run(&app_handle, &id).await?;
+    let history: Vec<Arc<ColumnEntry>> = run.entries.into_iter().map(Arc::new).collect();
+    let metadata = ExportMetadata {
+        operator: operator.as_deref().filter(|operator| !operator.is_empty()),
+        settings: run.header.settings.as_ref(),
+        utc_offset: utc_offset.unwrap_or_default(),
+    };
+    write_history(&path, &history, &metadata, &csv.unwrap_or_default())
+}
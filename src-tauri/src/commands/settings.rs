@@ -1,7 +1,17 @@
+use super::activity::{ActivityModel, ActivityModelKind};
+use super::error::AppError;
+use super::migrations::{backup_settings, migrate, settings_version, SETTINGS_VERSION};
+use super::modbus_serial::usb_ports;
+use super::registers::{PressureUnit, RegisterFormat, TemperatureUnit};
+use super::thermodynamics::{BinaryMixture, Mixture};
+use super::validation::validate_settings;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager, State};
 use tokio::sync::Mutex;
+use tokio::time::{Duration, MissedTickBehavior};
 
 #[derive(Default, Clone, Debug)]
 pub struct SettingsState {
@@ -14,77 +24,549 @@ impl SettingsState {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-#[serde(rename_all = "camelCase")]
+/// Missing fields take their value from `Settings::default`, so files written
+/// before a field was added still load. Changes that need more than a default
+/// bump `SETTINGS_VERSION` with a migration.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Settings {
+    pub version: u32,
     pub usb_port: String,
     pub baudrate: u32,
     pub temperature_address: TemperatureAddress,
-    pub count: u16,
+    /// Timeout of every sensor read, in milliseconds
     pub timeout: u64,
     pub unit_id: u8,
     pub number_plates: usize,
+    pub mixture: Mixture,
+    pub pressure: PressureSettings,
+    pub activity_model: ActivityModelKind,
+    /// Overrides the built-in parameters of the selected mixture
+    pub activity_parameters: Option<ActivityModel>,
+    pub plate_sensors: Vec<PlateSensor>,
+    pub serial: SerialLineSettings,
+    pub transport: Transport,
+    pub network: NetworkSettings,
+    pub probe: ProbeSettings,
+    pub history: HistorySettings,
+    pub acquisition: AcquisitionSettings,
+    pub simulation: SimulationSettings,
+}
+
+/// Written to a new settings file, valid except for the serial port to choose
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            usb_port: String::new(),
+            baudrate: 9600,
+            temperature_address: TemperatureAddress {
+                top: 101,
+                bottom: 100,
+            },
+            timeout: 1000,
+            unit_id: 10,
+            number_plates: 2,
+            mixture: Mixture::default(),
+            pressure: PressureSettings::default(),
+            activity_model: ActivityModelKind::default(),
+            activity_parameters: None,
+            plate_sensors: Vec::new(),
+            serial: SerialLineSettings::default(),
+            transport: Transport::default(),
+            network: NetworkSettings::default(),
+            probe: ProbeSettings::default(),
+            history: HistorySettings::default(),
+            acquisition: AcquisitionSettings::default(),
+            simulation: SimulationSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn mixture_model(&self) -> BinaryMixture {
+        let mut model = self.mixture.model(self.activity_model);
+        if let Some(parameters) = self.activity_parameters {
+            model.activity = parameters;
+        }
+        model
+    }
+
+    /// Plates with a temperature sensor. Without a sensor map the bottom and
+    /// top addresses are used for the first and last plates.
+    pub fn temperature_sensors(&self) -> Vec<PlateSensor> {
+        if !self.plate_sensors.is_empty() {
+            return self.plate_sensors.clone();
+        }
+        vec![
+            PlateSensor {
+                plate: 0,
+                address: self.temperature_address.bottom,
+                ..PlateSensor::default()
+            },
+            PlateSensor {
+                plate: self.number_plates.max(2) - 1,
+                address: self.temperature_address.top,
+                ..PlateSensor::default()
+            },
+        ]
+    }
+
+    /// Unit, address and register count read by the connection probe. Without an
+    /// explicit address the registers of the first temperature sensor are probed.
+    pub fn probe_target(&self) -> (u8, u16, u16) {
+        let sensor = self.temperature_sensors().into_iter().next();
+        let unit_id = self
+            .probe
+            .unit_id
+            .or(sensor.as_ref().and_then(|sensor| sensor.unit_id))
+            .unwrap_or(self.unit_id);
+        match (self.probe.address, sensor) {
+            (Some(address), _) => (unit_id, address, self.probe.count.max(1)),
+            (None, Some(sensor)) => (
+                unit_id,
+                sensor.address,
+                sensor.format.data_type.register_count(),
+            ),
+            (None, None) => (unit_id, self.temperature_address.bottom, 1),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct TemperatureAddress {
     pub top: u16,
     pub bottom: u16,
 }
 
+/// Temperature sensor of a single plate, plate 0 being the bottom of the column.
+/// `unit_id` defaults to the unit of the settings. The decoded register value is
+/// converted as `raw * scale + offset` in `unit`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlateSensor {
+    pub plate: usize,
+    pub address: u16,
+    pub unit_id: Option<u8>,
+    #[serde(flatten)]
+    pub format: RegisterFormat,
+    pub scale: f64,
+    pub offset: f64,
+    pub unit: TemperatureUnit,
+}
+
+impl Default for PlateSensor {
+    fn default() -> Self {
+        Self {
+            plate: 0,
+            address: 0,
+            unit_id: None,
+            format: RegisterFormat::default(),
+            scale: 0.01,
+            offset: 0.0,
+            unit: TemperatureUnit::default(),
+        }
+    }
+}
+
+/// Column pressure. `value` is the fixed pressure in mmHg used when no `address`
+/// is set, otherwise the register is read every cycle and converted as
+/// `raw * scale + offset` in `unit`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PressureSettings {
+    pub value: f64,
+    pub address: Option<u16>,
+    #[serde(flatten)]
+    pub format: RegisterFormat,
+    pub scale: f64,
+    pub offset: f64,
+    pub unit: PressureUnit,
+}
+
+impl Default for PressureSettings {
+    fn default() -> Self {
+        Self {
+            value: 585.0,
+            address: None,
+            format: RegisterFormat::default(),
+            scale: 0.1,
+            offset: 0.0,
+            unit: PressureUnit::default(),
+        }
+    }
+}
+
+/// Character frame of the serial line, 8N1 unless configured otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SerialLineSettings {
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+    pub flow_control: FlowControl,
+}
+
+impl Default for SerialLineSettings {
+    fn default() -> Self {
+        Self {
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+            flow_control: FlowControl::None,
+        }
+    }
+}
+
+impl SerialLineSettings {
+    /// Serial settings for rodbus at `baudrate`, or the reason the line can't be opened
+    pub fn to_serial_settings(self, baudrate: u32) -> Result<rodbus::SerialSettings, AppError> {
+        if baudrate == 0 {
+            return Err(AppError::settings(
+                "baudrate",
+                "Baudrate must be greater than 0",
+            ));
+        }
+        let data_bits = match self.data_bits {
+            7 => rodbus::DataBits::Seven,
+            8 => rodbus::DataBits::Eight,
+            bits => {
+                return Err(AppError::settings(
+                    "serial.dataBits",
+                    format!("Unsupported data bits {}, expected 7 or 8", bits),
+                ))
+            }
+        };
+        let stop_bits = match self.stop_bits {
+            1 => rodbus::StopBits::One,
+            2 => rodbus::StopBits::Two,
+            bits => {
+                return Err(AppError::settings(
+                    "serial.stopBits",
+                    format!("Unsupported stop bits {}, expected 1 or 2", bits),
+                ))
+            }
+        };
+
+        Ok(rodbus::SerialSettings {
+            baud_rate: baudrate,
+            data_bits,
+            stop_bits,
+            parity: match self.parity {
+                Parity::None => rodbus::Parity::None,
+                Parity::Odd => rodbus::Parity::Odd,
+                Parity::Even => rodbus::Parity::Even,
+            },
+            flow_control: match self.flow_control {
+                FlowControl::None => rodbus::FlowControl::None,
+                FlowControl::Software => rodbus::FlowControl::Software,
+                FlowControl::Hardware => rodbus::FlowControl::Hardware,
+            },
+        })
+    }
+}
+
+/// Short form of the frame such as `8E1`
+impl fmt::Display for SerialLineSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        write!(f, "{}{}{}", self.data_bits, parity, self.stop_bits)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FlowControl {
+    #[default]
+    None,
+    Software,
+    Hardware,
+}
+
+/// Link to the Modbus devices. `RtuSerial` uses `usb_port` and `baudrate`, the
+/// TCP transports connect to the gateway in `network`. `Simulated` answers from
+/// a simulated column as set in `simulation`, for use without the hardware.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Transport {
+    #[default]
+    RtuSerial,
+    Tcp,
+    RtuOverTcp,
+    Simulated,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 502,
+        }
+    }
+}
+
+/// Request used to check the link on connect. `address` and `unit_id` default to
+/// the first temperature sensor. Every failed attempt waits `backoff_ms`, doubled
+/// after each retry.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProbeSettings {
+    pub function: ProbeFunction,
+    pub address: Option<u16>,
+    pub count: u16,
+    pub unit_id: Option<u8>,
+    pub retries: u32,
+    pub timeout_ms: u64,
+    pub backoff_ms: u64,
+}
+
+impl Default for ProbeSettings {
+    fn default() -> Self {
+        Self {
+            function: ProbeFunction::default(),
+            address: None,
+            count: 1,
+            unit_id: None,
+            retries: 2,
+            timeout_ms: 1000,
+            backoff_ms: 1000,
+        }
+    }
+}
+
+/// Object type read by the probe
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeFunction {
+    Coils,
+    DiscreteInputs,
+    #[default]
+    HoldingRegisters,
+    InputRegisters,
+}
+
+/// Retention of the in-memory history. Raw entries are kept for
+/// `raw_retention_minutes`, the min, max and mean of every `bucket_seconds`
+/// for `bucket_retention_hours`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySettings {
+    pub raw_retention_minutes: u64,
+    pub bucket_seconds: u64,
+    pub bucket_retention_hours: u64,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            raw_retention_minutes: 360,
+            bucket_seconds: 60,
+            bucket_retention_hours: 168,
+        }
+    }
+}
+
+/// Period of the acquisition cycle. Cycles are scheduled on a fixed interval so
+/// the Modbus round trip does not add to the period; ticks missed by a slow
+/// cycle are handled as set by `missed_ticks`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AcquisitionSettings {
+    pub sample_period_ms: u64,
+    pub missed_ticks: MissedTicks,
+}
+
+impl AcquisitionSettings {
+    pub const MIN_SAMPLE_PERIOD_MS: u64 = 100;
+    pub const MAX_SAMPLE_PERIOD_MS: u64 = 600_000;
+
+    pub fn sample_period(&self) -> Duration {
+        Duration::from_millis(
+            self.sample_period_ms
+                .clamp(Self::MIN_SAMPLE_PERIOD_MS, Self::MAX_SAMPLE_PERIOD_MS),
+        )
+    }
+}
+
+impl Default for AcquisitionSettings {
+    fn default() -> Self {
+        Self {
+            sample_period_ms: 1000,
+            missed_ticks: MissedTicks::default(),
+        }
+    }
+}
+
+/// What to do with the ticks missed while a cycle overran the period
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MissedTicks {
+    /// Drop them and stay on the original schedule
+    #[default]
+    Skip,
+    /// Run them back to back until caught up
+    Burst,
+    /// Restart the schedule from the late cycle
+    Delay,
+}
+
+impl MissedTicks {
+    pub fn behavior(self) -> MissedTickBehavior {
+        match self {
+            MissedTicks::Skip => MissedTickBehavior::Skip,
+            MissedTicks::Burst => MissedTickBehavior::Burst,
+            MissedTicks::Delay => MissedTickBehavior::Delay,
+        }
+    }
+}
+
+/// Column simulated by the `Simulated` transport. The column heats up from
+/// `ambient_temperature` bottom to top over `heat_up_minutes`, then settles on
+/// the profile of the reflux ratio, which steps to a random value between
+/// `reflux_min` and `reflux_max` every `reflux_change_minutes` (never when 0).
+/// `noise` is the standard deviation of every reading, in °C or in mmHg for the
+/// pressure, and `fault_rate` the chance of a read starting a sensor fault.
+/// `time_scale` speeds up the simulated clock and `seed` makes a session
+/// repeatable.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SimulationSettings {
+    pub ambient_temperature: f64,
+    pub heat_up_minutes: f64,
+    /// Mole fraction of the light component in the feed
+    pub feed_composition: f64,
+    pub reflux_min: f64,
+    pub reflux_max: f64,
+    pub reflux_change_minutes: f64,
+    pub noise: f64,
+    pub fault_rate: f64,
+    pub time_scale: f64,
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            ambient_temperature: 25.0,
+            heat_up_minutes: 20.0,
+            feed_composition: 0.3,
+            reflux_min: 1.0,
+            reflux_max: 5.0,
+            reflux_change_minutes: 30.0,
+            noise: 0.1,
+            fault_rate: 0.001,
+            time_scale: 1.0,
+            seed: None,
+        }
+    }
+}
+
 const SETTINGS_FILE: &str = "settings.json";
 
-pub async fn ensure_settings_file(app_handle: AppHandle) -> String {
-    let app_data_dir = app_handle.path().app_config_dir().unwrap();
+pub async fn ensure_settings_file(app_handle: AppHandle) -> Result<PathBuf, AppError> {
+    let app_data_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::file("app config dir", e))?;
     if !app_data_dir.exists() {
         println!("Creating app data directory...");
-        match fs::create_dir(app_data_dir.clone()) {
-            Ok(_) => println!("App data directory created"),
-            Err(e) => println!("Error creating app data directory: {:?}", e),
-        }
+        fs::create_dir_all(&app_data_dir)
+            .map_err(|e| AppError::file(app_data_dir.display().to_string(), e))?;
+        println!("App data directory created");
     }
 
     let settings_file = app_data_dir.join(SETTINGS_FILE);
     if !settings_file.exists() {
         println!("Creating settings file...");
-        let settings = Settings::default();
-        let settings_json = serde_json::to_string(&settings).unwrap();
-        match fs::write(settings_file.clone(), settings_json) {
-            Ok(_) => println!("Settings file created"),
-            Err(e) => println!("Error creating settings file: {:?}", e),
-        }
+        write_settings(&settings_file, &Settings::default())?;
+        println!("Settings file created");
     }
-    return settings_file.to_str().unwrap().to_string();
+    Ok(settings_file)
+}
+
+fn write_settings(path: &Path, settings: &Settings) -> Result<(), AppError> {
+    let settings_json =
+        serde_json::to_string(settings).map_err(|e| AppError::parse("serializing settings", e))?;
+    fs::write(path, settings_json).map_err(|e| AppError::file(path.display().to_string(), e))
 }
 
 #[tauri::command]
-pub async fn save_settings(app_handle: AppHandle, settings: Settings) {
-    let app_data_dir = ensure_settings_file(app_handle).await;
-    let settings_json = serde_json::to_string(&settings).unwrap();
-    match fs::write(app_data_dir, settings_json) {
-        Ok(_) => println!("Settings saved: {:?}", settings),
-        Err(e) => println!("Error saving settings: {:?}", e),
+pub async fn save_settings(app_handle: AppHandle, mut settings: Settings) -> Result<(), AppError> {
+    settings.version = SETTINGS_VERSION;
+    // the port is only checked if the adapters can be listed
+    let ports = match settings.transport {
+        Transport::RtuSerial => usb_ports().ok(),
+        _ => None,
+    };
+    validate_settings(&settings, ports.as_deref())?;
+    let settings_file = ensure_settings_file(app_handle).await?;
+    // a newer build's file would lose its fields, keep it for when it comes back
+    let version = fs::read_to_string(&settings_file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .map_or(0, |value| settings_version(&value));
+    if version > SETTINGS_VERSION {
+        backup_settings(&settings_file, version)?;
     }
+    write_settings(&settings_file, &settings)?;
+    println!("Settings saved: {:?}", settings);
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn get_settings(
     app_handle: AppHandle,
     settings_state: State<'_, Mutex<SettingsState>>,
-) -> Result<Settings, Settings> {
+) -> Result<Settings, AppError> {
     println!("Loading settings...");
-    let app_data_dir = ensure_settings_file(app_handle).await;
-    match fs::read_to_string(app_data_dir) {
-        Ok(settings_json) => {
-            let new_settings: Settings = serde_json::from_str(&settings_json).unwrap();
-            let mut settings = settings_state.lock().await;
-            settings.set_settings(new_settings.clone());
-            println!("Settings succesfully loaded");
-            return Ok(new_settings);
-        }
-        Err(e) => {
-            println!("Error loading settings: {:?}", e);
-            return Err(Settings::default());
-        }
-    };
+    let settings_file = ensure_settings_file(app_handle).await?;
+    let settings_json = fs::read_to_string(&settings_file)
+        .map_err(|e| AppError::file(settings_file.display().to_string(), e))?;
+    let mut value: serde_json::Value = serde_json::from_str(&settings_json)
+        .map_err(|e| AppError::parse(settings_file.display().to_string(), e))?;
+
+    let version = settings_version(&value);
+    if version > SETTINGS_VERSION {
+        eprintln!(
+            "Settings file version {} is newer than {}, unknown fields are ignored",
+            version, SETTINGS_VERSION
+        );
+    }
+    let migrating = version < SETTINGS_VERSION;
+    if migrating {
+        backup_settings(&settings_file, version)?;
+        migrate(&mut value, version)?;
+    }
+    let new_settings: Settings = serde_json::from_value(value)
+        .map_err(|e| AppError::parse(settings_file.display().to_string(), e))?;
+    if migrating {
+        write_settings(&settings_file, &new_settings)?;
+    }
+    validate_settings(&new_settings, None)?;
+
+    let mut settings = settings_state.lock().await;
+    settings.set_settings(new_settings.clone());
+    println!("Settings succesfully loaded");
+    Ok(new_settings)
 }
@@ -0,0 +1,361 @@
+use super::calculations::calculate_bubble_temperature;
+use super::registers::RegisterFormat;
+use super::settings::{PlateSensor, PressureSettings, Settings, SimulationSettings};
+use super::thermodynamics::BinaryMixture;
+use rodbus::client::RequestParam;
+use rodbus::{AddressRange, Indexed, RequestError};
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Light component mole fraction reached at the top with total reflux
+const TOP_COMPOSITION: f64 = 0.85;
+/// Steepness of the composition profile along the column
+const PROFILE_SHAPE: f64 = 6.0;
+/// Reading of a sensor with a broken element, in °C
+const SPIKE_TEMPERATURE: f64 = 850.0;
+/// Reads a sensor fault lasts
+const FAULT_READS: std::ops::Range<u64> = 3..20;
+
+#[derive(Debug, Clone, Copy)]
+enum Fault {
+    /// The device does not answer
+    Timeout,
+    /// The reading stops changing
+    Stuck,
+    /// The reading jumps far out of range
+    Spike,
+}
+
+struct ActiveFault {
+    fault: Fault,
+    reads_left: u64,
+}
+
+/// In-process Modbus device simulating a distillation column. Plate temperatures
+/// follow the startup heat-up and the reflux ratio changes with a first order
+/// lag, and are served on the holding and input registers of the configured
+/// sensors, encoded as their format, scale and unit. The pressure register is
+/// served the same way when set. Other registers and coils hold what was last
+/// written to them.
+pub struct ColumnSimulator {
+    simulation: SimulationSettings,
+    mixture: BinaryMixture,
+    pressure: PressureSettings,
+    sensors: Vec<PlateSensor>,
+    started: Instant,
+    /// Simulated seconds since the start of the last update
+    clock: f64,
+    temperatures: Vec<f64>,
+    reflux: f64,
+    next_reflux_change: f64,
+    /// Steady state temperature of every plate at the current reflux
+    profile: Vec<f64>,
+    faults: HashMap<u16, ActiveFault>,
+    /// Last words served for every sensor address, repeated while stuck
+    last_words: HashMap<u16, Vec<u16>>,
+    registers: HashMap<u16, u16>,
+    coils: HashMap<u16, bool>,
+    rng: Rng,
+}
+
+impl ColumnSimulator {
+    pub fn new(settings: &Settings) -> Self {
+        let simulation = settings.simulation.clone();
+        let plates = settings.number_plates.max(2);
+        let seed = simulation.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        let mut simulator = Self {
+            mixture: settings.mixture_model(),
+            pressure: settings.pressure.clone(),
+            sensors: settings.temperature_sensors(),
+            started: Instant::now(),
+            clock: 0.0,
+            temperatures: vec![simulation.ambient_temperature; plates],
+            reflux: simulation.reflux_min,
+            next_reflux_change: simulation.reflux_change_minutes * 60.0,
+            profile: Vec::new(),
+            faults: HashMap::new(),
+            last_words: HashMap::new(),
+            registers: HashMap::new(),
+            coils: HashMap::new(),
+            rng: Rng::new(seed),
+            simulation,
+        };
+        simulator.profile = simulator.profile(simulator.reflux);
+        simulator
+    }
+
+    /// Steady state plate temperatures at `reflux`. The light component goes
+    /// from depleted at the bottom to enriched at the top, more so the higher
+    /// the reflux ratio, and every plate is at the bubble point of its liquid.
+    fn profile(&self, reflux: f64) -> Vec<f64> {
+        let plates = self.temperatures.len();
+        let separation = reflux / (reflux + 1.0);
+        let feed = self.simulation.feed_composition.clamp(0.01, 0.99);
+        let bottom = feed * (1.0 - 0.9 * separation);
+        let top = feed + (TOP_COMPOSITION.max(feed) - feed) * separation;
+
+        let sigmoid = |u: f64| 1.0 / (1.0 + (-PROFILE_SHAPE * (u - 0.5)).exp());
+        let (low, high) = (sigmoid(0.0), sigmoid(1.0));
+        let (t_light, t_heavy) = self.mixture.boiling_range(self.pressure.value);
+        (0..plates)
+            .map(|plate| {
+                let u = plate as f64 / (plates - 1) as f64;
+                let x = bottom + (top - bottom) * (sigmoid(u) - low) / (high - low);
+                calculate_bubble_temperature(&self.mixture, self.pressure.value, x, 1e-6, 1000)
+                    .map(|result| result.value)
+                    .unwrap_or(t_heavy + (t_light - t_heavy) * x)
+            })
+            .collect()
+    }
+
+    /// Moves the column to the current simulated time
+    fn advance(&mut self) {
+        let now = self.started.elapsed().as_secs_f64() * self.simulation.time_scale.max(0.0);
+        let dt = now - self.clock;
+        self.clock = now;
+
+        let change_every = self.simulation.reflux_change_minutes * 60.0;
+        if change_every > 0.0 && now >= self.next_reflux_change {
+            let (min, max) = (self.simulation.reflux_min, self.simulation.reflux_max);
+            self.reflux = min + (max - min).max(0.0) * self.rng.uniform();
+            self.next_reflux_change = now + change_every;
+            self.profile = self.profile(self.reflux);
+            println!("Simulated reflux ratio changed to {:.2}", self.reflux);
+        }
+
+        // the heat reaches the plates one by one from the reboiler
+        let plates = self.temperatures.len();
+        let heat_up = self.simulation.heat_up_minutes.max(0.0) * 60.0;
+        let lag = (heat_up / (2.0 * plates as f64)).max(10.0);
+        let approach = 1.0 - (-dt / lag).exp();
+        for (plate, temperature) in self.temperatures.iter_mut().enumerate() {
+            let reached = now >= heat_up * plate as f64 / plates as f64;
+            let target = if reached {
+                self.profile[plate]
+            } else {
+                self.simulation.ambient_temperature
+            };
+            *temperature += (target - *temperature) * approach;
+        }
+    }
+
+    /// Words of every register in `range`, failing as a faulty device would
+    fn read_registers(&mut self, range: AddressRange) -> Result<Vec<u16>, RequestError> {
+        self.advance();
+        let addresses = range.start as u32..range.start as u32 + range.count as u32;
+        let mut words = self.registers.clone();
+
+        for sensor in self.sensors.clone() {
+            let count = sensor.format.data_type.register_count() as u32;
+            let start = sensor.address as u32;
+            if start >= addresses.end || start + count <= addresses.start {
+                continue;
+            }
+            let temperature = self
+                .temperatures
+                .get(sensor.plate)
+                .copied()
+                .unwrap_or(self.simulation.ambient_temperature)
+                + self.simulation.noise * self.rng.gaussian();
+            let raw = (sensor.unit.celsius_to_unit(temperature) - sensor.offset) / sensor.scale;
+            let encoded = self.sensor_words(sensor.address, &sensor.format, raw, |spike| {
+                (sensor.unit.celsius_to_unit(spike) - sensor.offset) / sensor.scale
+            })?;
+            words.extend((sensor.address..).zip(encoded));
+        }
+
+        if let Some(address) = self.pressure.address {
+            let pressure = &self.pressure;
+            let value = pressure.value + self.simulation.noise * self.rng.gaussian();
+            let raw = (pressure.unit.mmhg_to_unit(value) - pressure.offset) / pressure.scale;
+            let encoded = pressure.format.encode(raw);
+            words.extend((address..).zip(encoded));
+        }
+
+        Ok(addresses
+            .map(|address| words.get(&(address as u16)).copied().unwrap_or_default())
+            .collect())
+    }
+
+    /// Encoded reading of a sensor, starting and applying its faults
+    fn sensor_words(
+        &mut self,
+        address: u16,
+        format: &RegisterFormat,
+        raw: f64,
+        spike_raw: impl Fn(f64) -> f64,
+    ) -> Result<Vec<u16>, RequestError> {
+        if !self.faults.contains_key(&address) && self.rng.uniform() < self.simulation.fault_rate {
+            let fault = match self.rng.below(3) {
+                0 => Fault::Timeout,
+                1 => Fault::Stuck,
+                _ => Fault::Spike,
+            };
+            let reads_left =
+                FAULT_READS.start + self.rng.below(FAULT_READS.end - FAULT_READS.start);
+            println!(
+                "Simulated {:?} fault on sensor {} for {} reads",
+                fault, address, reads_left
+            );
+            self.faults
+                .insert(address, ActiveFault { fault, reads_left });
+        }
+
+        let fault = self.faults.get_mut(&address).map(|active| {
+            active.reads_left -= 1;
+            active.fault
+        });
+        if self
+            .faults
+            .get(&address)
+            .is_some_and(|active| active.reads_left == 0)
+        {
+            self.faults.remove(&address);
+        }
+
+        let words = match fault {
+            Some(Fault::Timeout) => return Err(RequestError::ResponseTimeout),
+            Some(Fault::Stuck) => match self.last_words.get(&address) {
+                Some(words) => return Ok(words.clone()),
+                None => format.encode(raw),
+            },
+            Some(Fault::Spike) => format.encode(spike_raw(SPIKE_TEMPERATURE)),
+            None => format.encode(raw),
+        };
+        self.last_words.insert(address, words.clone());
+        Ok(words)
+    }
+
+    fn read_bits(&self, range: AddressRange) -> Vec<Indexed<bool>> {
+        (range.start..)
+            .take(range.count as usize)
+            .map(|address| {
+                Indexed::new(
+                    address,
+                    self.coils.get(&address).copied().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn read_coils(
+        &mut self,
+        _param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        Ok(self.read_bits(range))
+    }
+
+    pub fn read_discrete_inputs(
+        &mut self,
+        _param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, RequestError> {
+        Ok(self.read_bits(range))
+    }
+
+    pub fn read_holding_registers(
+        &mut self,
+        _param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        let words = self.read_registers(range)?;
+        Ok((range.start..)
+            .zip(words)
+            .map(|(address, word)| Indexed::new(address, word))
+            .collect())
+    }
+
+    pub fn read_input_registers(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        self.read_holding_registers(param, range)
+    }
+
+    pub fn write_single_coil(
+        &mut self,
+        _param: RequestParam,
+        request: Indexed<bool>,
+    ) -> Result<Indexed<bool>, RequestError> {
+        self.coils.insert(request.index, request.value);
+        Ok(request)
+    }
+
+    pub fn write_single_register(
+        &mut self,
+        _param: RequestParam,
+        request: Indexed<u16>,
+    ) -> Result<Indexed<u16>, RequestError> {
+        self.registers.insert(request.index, request.value);
+        Ok(request)
+    }
+
+    pub fn write_multiple_coils(
+        &mut self,
+        _param: RequestParam,
+        start: u16,
+        values: Vec<bool>,
+    ) -> Result<AddressRange, RequestError> {
+        let range = write_range(start, values.len())?;
+        self.coils.extend((start..).zip(values));
+        Ok(range)
+    }
+
+    pub fn write_multiple_registers(
+        &mut self,
+        _param: RequestParam,
+        start: u16,
+        values: Vec<u16>,
+    ) -> Result<AddressRange, RequestError> {
+        let range = write_range(start, values.len())?;
+        self.registers.extend((start..).zip(values));
+        Ok(range)
+    }
+}
+
+fn write_range(start: u16, count: usize) -> Result<AddressRange, RequestError> {
+    let count = u16::try_from(count).unwrap_or(u16::MAX);
+    Ok(AddressRange::try_from(start, count)?)
+}
+
+/// Small xorshift generator, good enough for noise and faults
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Self((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound.max(1)
+    }
+
+    /// Standard normal, by the Box-Muller transform
+    fn gaussian(&mut self) -> f64 {
+        let u1 = self.uniform().max(f64::MIN_POSITIVE);
+        let u2 = self.uniform();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
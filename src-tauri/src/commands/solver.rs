@@ -0,0 +1,358 @@
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SolverMethod {
+    Newton,
+    Brent,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverResult {
+    pub value: f64,
+    pub iterations: u64,
+    pub residual: f64,
+    pub method: SolverMethod,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SolverError {
+    /// The residual has the same sign at both ends of the interval
+    NoBracket { f_low: f64, f_high: f64 },
+    NotConverged {
+        value: f64,
+        iterations: u64,
+        residual: f64,
+    },
+    /// The residual evaluated to NaN or infinity
+    InvalidResidual { value: f64 },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::NoBracket { f_low, f_high } => write!(
+                f,
+                "No root in interval, residuals {:.3e} and {:.3e} have the same sign",
+                f_low, f_high
+            ),
+            SolverError::NotConverged {
+                value,
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "Not converged after {} iterations (x = {}, residual = {:.3e})",
+                iterations, value, residual
+            ),
+            SolverError::InvalidResidual { value } => {
+                write!(f, "Invalid residual at x = {}", value)
+            }
+        }
+    }
+}
+
+/// Finds a root of `f` inside [low, high] starting from `x_0`.
+///
+/// Newton steps with a central difference derivative are taken while they stay
+/// inside the current bracket. When a step leaves it, the derivative vanishes or
+/// the residual is not finite, the search falls back to Brent's method.
+pub fn solve_bracketed<F: Fn(f64) -> f64>(
+    f: F,
+    low: f64,
+    high: f64,
+    x_0: f64,
+    tol: f64,
+    max_iter: u64,
+) -> Result<SolverResult, SolverError> {
+    let (mut a, mut b) = (low, high);
+    let (mut fa, mut fb) = (f(a), f(b));
+
+    if !fa.is_finite() {
+        return Err(SolverError::InvalidResidual { value: a });
+    }
+    if !fb.is_finite() {
+        return Err(SolverError::InvalidResidual { value: b });
+    }
+    // pure components sit exactly on the interval ends
+    for (value, residual) in [(a, fa), (b, fb)] {
+        if residual.abs() < 1e-12 {
+            return Ok(SolverResult {
+                value,
+                iterations: 0,
+                residual,
+                method: SolverMethod::Newton,
+            });
+        }
+    }
+    if fa.signum() == fb.signum() {
+        return Err(SolverError::NoBracket {
+            f_low: fa,
+            f_high: fb,
+        });
+    }
+
+    let h = (high - low) * 1e-6;
+    let mut x = x_0.clamp(low, high);
+
+    for iteration in 1..=max_iter {
+        let fx = f(x);
+        if !fx.is_finite() {
+            return brent(&f, a, b, fa, fb, tol, max_iter, iteration);
+        }
+        if fx == 0.0 {
+            return Ok(SolverResult {
+                value: x,
+                iterations: iteration,
+                residual: fx,
+                method: SolverMethod::Newton,
+            });
+        }
+
+        // keep the bracket as tight as the iterates allow
+        if fx.signum() == fa.signum() {
+            a = x;
+            fa = fx;
+        } else {
+            b = x;
+            fb = fx;
+        }
+
+        let (x_minus, x_plus) = ((x - h).max(low), (x + h).min(high));
+        let derivative = (f(x_plus) - f(x_minus)) / (x_plus - x_minus);
+        if !derivative.is_finite() || derivative.abs() < 1e-12 {
+            return brent(&f, a, b, fa, fb, tol, max_iter, iteration);
+        }
+
+        let x_next = x - fx / derivative;
+        if x_next <= a.min(b) || x_next >= a.max(b) {
+            return brent(&f, a, b, fa, fb, tol, max_iter, iteration);
+        }
+
+        if (x_next - x).abs() < tol {
+            return Ok(SolverResult {
+                value: x_next,
+                iterations: iteration,
+                residual: f(x_next),
+                method: SolverMethod::Newton,
+            });
+        }
+
+        x = x_next;
+    }
+
+    Err(SolverError::NotConverged {
+        value: x,
+        iterations: max_iter,
+        residual: f(x),
+    })
+}
+
+/// `solve_bracketed` for residuals that can have several roots in [low, high],
+/// such as the bubble point of an azeotropic mixture. When both ends have the
+/// same sign, the interval is split into `segments` and the sign change closest
+/// to `x_0` is solved.
+pub fn solve_segmented<F: Fn(f64) -> f64>(
+    f: F,
+    low: f64,
+    high: f64,
+    x_0: f64,
+    tol: f64,
+    max_iter: u64,
+    segments: usize,
+) -> Result<SolverResult, SolverError> {
+    let no_bracket = match solve_bracketed(&f, low, high, x_0, tol, max_iter) {
+        Err(error @ SolverError::NoBracket { .. }) => error,
+        result => return result,
+    };
+
+    let width = (high - low) / segments.max(1) as f64;
+    let points: Vec<(f64, f64)> = (0..=segments)
+        .map(|i| {
+            let x = if i == segments {
+                high
+            } else {
+                low + width * i as f64
+            };
+            (x, f(x))
+        })
+        .collect();
+    // zero when x_0 lies inside [a, b]
+    let distance = |(a, b): (f64, f64)| (a - x_0).max(x_0 - b).max(0.0);
+    points
+        .windows(2)
+        .filter(|pair| {
+            let ((_, fa), (_, fb)) = (pair[0], pair[1]);
+            fa.is_finite() && fb.is_finite() && fa.signum() != fb.signum()
+        })
+        .map(|pair| (pair[0].0, pair[1].0))
+        .min_by(|first, second| distance(*first).total_cmp(&distance(*second)))
+        .map_or(Err(no_bracket), |(a, b)| {
+            solve_bracketed(&f, a, b, x_0.clamp(a, b), tol, max_iter)
+        })
+}
+
+/// Brent's method on a bracket [a, b] where f(a) and f(b) have opposite signs.
+/// `used` is the number of iterations already spent by the caller.
+#[allow(clippy::too_many_arguments)]
+fn brent<F: Fn(f64) -> f64>(
+    f: &F,
+    mut a: f64,
+    mut b: f64,
+    mut fa: f64,
+    mut fb: f64,
+    tol: f64,
+    max_iter: u64,
+    used: u64,
+) -> Result<SolverResult, SolverError> {
+    let (mut c, mut fc) = (b, fb);
+    let mut d = b - a;
+    let mut e = d;
+
+    for iteration in used..=max_iter {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+
+        let tol_1 = 2.0 * f64::EPSILON * b.abs() + 0.5 * tol;
+        let middle = 0.5 * (c - b);
+        if middle.abs() <= tol_1 || fb == 0.0 {
+            return Ok(SolverResult {
+                value: b,
+                iterations: iteration,
+                residual: fb,
+                method: SolverMethod::Brent,
+            });
+        }
+
+        if e.abs() >= tol_1 && fa.abs() > fb.abs() {
+            // inverse quadratic interpolation, or secant when only two points are known
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * middle * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * middle * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            let min_1 = 3.0 * middle * q - (tol_1 * q).abs();
+            let min_2 = (e * q).abs();
+            if 2.0 * p < min_1.min(min_2) {
+                e = d;
+                d = p / q;
+            } else {
+                d = middle;
+                e = d;
+            }
+        } else {
+            d = middle;
+            e = d;
+        }
+
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol_1 {
+            d
+        } else {
+            tol_1.copysign(middle)
+        };
+        fb = f(b);
+        if !fb.is_finite() {
+            return Err(SolverError::InvalidResidual { value: b });
+        }
+    }
+
+    Err(SolverError::NotConverged {
+        value: b,
+        iterations: max_iter,
+        residual: fb,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges_to_root_inside_bracket() {
+        let result = solve_bracketed(|x| x * x - 2.0, 0.0, 2.0, 1.0, 1e-10, 50).unwrap();
+        assert!((result.value - 2f64.sqrt()).abs() < 1e-9);
+        assert!(result.residual.abs() < 1e-9);
+        assert_eq!(result.method, SolverMethod::Newton);
+    }
+
+    #[test]
+    fn falls_back_to_brent_when_newton_leaves_bracket() {
+        // the flat tail sends the first Newton step far outside [-1, 10]
+        let result =
+            solve_bracketed(|x: f64| x.atan() - 0.5, -1.0, 10.0, 10.0, 1e-10, 100).unwrap();
+        assert!((result.value - 0.5f64.tan()).abs() < 1e-8);
+        assert_eq!(result.method, SolverMethod::Brent);
+    }
+
+    #[test]
+    fn rejects_interval_without_sign_change() {
+        let result = solve_bracketed(|x| x * x + 1.0, -1.0, 1.0, 0.0, 1e-10, 50);
+        assert_eq!(
+            result.unwrap_err(),
+            SolverError::NoBracket {
+                f_low: 2.0,
+                f_high: 2.0
+            }
+        );
+    }
+
+    #[test]
+    fn segments_find_root_pair_without_end_sign_change() {
+        // roots at 0.3 and 0.7, negative at both ends
+        let f = |x: f64| -(x - 0.3) * (x - 0.7);
+        assert!(matches!(
+            solve_bracketed(f, 0.0, 1.0, 0.5, 1e-10, 50),
+            Err(SolverError::NoBracket { .. })
+        ));
+        let low = solve_segmented(f, 0.0, 1.0, 0.1, 1e-10, 50, 10).unwrap();
+        assert!((low.value - 0.3).abs() < 1e-9);
+        let high = solve_segmented(f, 0.0, 1.0, 0.9, 1e-10, 50, 10).unwrap();
+        assert!((high.value - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn segments_report_no_bracket_without_root() {
+        let result = solve_segmented(|x| x * x + 1.0, -1.0, 1.0, 0.0, 1e-10, 50, 10);
+        assert!(matches!(result, Err(SolverError::NoBracket { .. })));
+    }
+
+    #[test]
+    fn rejects_non_finite_residual_at_interval_end() {
+        let result = solve_bracketed(|x: f64| x.ln(), 0.0, 2.0, 1.0, 1e-10, 50);
+        assert_eq!(
+            result.unwrap_err(),
+            SolverError::InvalidResidual { value: 0.0 }
+        );
+    }
+}
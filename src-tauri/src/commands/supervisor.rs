@@ -0,0 +1,274 @@
+use super::error::AppError;
+use super::modbus_serial::{CurrentConnection, ModbusClient};
+use super::settings::{Settings, SettingsState};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Consecutive failed cycles before the link is considered lost and reopened
+const FAILURES_BEFORE_RECONNECT: u32 = 3;
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkState {
+    #[default]
+    Disconnected,
+    Connected,
+    /// Reads are failing but the link has not been reopened yet
+    Degraded,
+    Reconnecting,
+}
+
+/// Health of the Modbus link, emitted to the frontend as `connection_status`
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkHealth {
+    pub state: LinkState,
+    pub consecutive_failures: u32,
+    /// Milliseconds since the epoch of the last successful cycle
+    pub last_success: Option<u64>,
+    pub last_error: Option<AppError>,
+    pub reconnect_attempts: u32,
+    #[serde(skip)]
+    next_reconnect: Option<Instant>,
+}
+
+impl LinkHealth {
+    pub fn connected() -> Self {
+        Self {
+            state: LinkState::Connected,
+            last_success: Some(now_millis()),
+            ..Self::default()
+        }
+    }
+
+    fn record_failure(&mut self, error: AppError) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error);
+        if self.state == LinkState::Connected {
+            self.state = LinkState::Degraded;
+        }
+    }
+
+    fn should_reconnect(&self) -> bool {
+        self.consecutive_failures >= FAILURES_BEFORE_RECONNECT
+            && self
+                .next_reconnect
+                .is_none_or(|next| Instant::now() >= next)
+    }
+}
+
+/// Records a successful acquisition cycle, emitting the status when the link recovers
+pub async fn report_success(
+    app_handle: &AppHandle,
+    connection_state: &State<'_, Mutex<CurrentConnection>>,
+) {
+    let mut current_connection = connection_state.lock().await;
+    if !current_connection.is_connected() {
+        return;
+    }
+    let recovered = current_connection.health.state != LinkState::Connected;
+    current_connection.health = LinkHealth::connected();
+    if recovered {
+        emit_connection_status(app_handle, &current_connection.health);
+    }
+}
+
+/// Records a failed acquisition cycle. Once the link is considered lost it is
+/// reopened from the current settings, waiting the probe backoff doubled after
+/// every failed attempt. Fails when there is no connection left to supervise.
+pub async fn report_failure(
+    app_handle: &AppHandle,
+    settings_state: &State<'_, Mutex<SettingsState>>,
+    connection_state: &State<'_, Mutex<CurrentConnection>>,
+    error: AppError,
+    token: &CancellationToken,
+) -> Result<(), AppError> {
+    let settings = {
+        let current_settings = settings_state.lock().await;
+        current_settings.settings.clone()
+    };
+    supervise_failure(connection_state, settings, error, token, |health| {
+        emit_connection_status(app_handle, health)
+    })
+    .await
+}
+
+/// `report_failure` with every status change passed to `emit`. A reconnect
+/// cancelled by `token` leaves the link disconnected, as the old client is
+/// already shut down.
+async fn supervise_failure(
+    connection_state: &Mutex<CurrentConnection>,
+    settings: Option<Settings>,
+    error: AppError,
+    token: &CancellationToken,
+    emit: impl Fn(&LinkHealth),
+) -> Result<(), AppError> {
+    let previous = {
+        let mut current_connection = connection_state.lock().await;
+        let Some(previous) = current_connection.client() else {
+            return Err(error);
+        };
+        current_connection.health.record_failure(error);
+        let reconnect = current_connection.health.should_reconnect();
+        if reconnect {
+            current_connection.health.state = LinkState::Reconnecting;
+        }
+        emit(&current_connection.health);
+        if !reconnect {
+            return Ok(());
+        }
+        previous
+    };
+    let settings = settings.ok_or(AppError::no_data("No settings found"))?;
+
+    // the connection lock is released while reconnecting so commands fail fast
+    let reconnect = async {
+        // the old channel would keep retrying the port the new one opens
+        let simulator = {
+            let mut previous = previous.lock().await;
+            let simulator = previous.simulator();
+            previous.shutdown().await;
+            simulator
+        };
+        ModbusClient::reconnect(&settings, simulator).await
+    };
+    let result = tokio::select! {
+        _ = token.cancelled() => None,
+        result = reconnect => Some(result),
+    };
+
+    let mut current_connection = connection_state.lock().await;
+    if !current_connection.is_connected() {
+        // disconnected by the user in the meantime
+        return Err(AppError::NotConnected);
+    }
+    let Some(result) = result else {
+        // a connection opened by the user in the meantime is kept
+        let abandoned = current_connection
+            .client()
+            .is_some_and(|client| Arc::ptr_eq(&client, &previous));
+        if abandoned {
+            current_connection.clear_connection();
+            emit(&current_connection.health);
+        }
+        return Ok(());
+    };
+    match result {
+        Ok(client) => {
+            println!("Reconnected to {}", ModbusClient::describe(&settings));
+            current_connection.set_connection(client);
+        }
+        Err(e) => {
+            let health = &mut current_connection.health;
+            health.reconnect_attempts += 1;
+            health.last_error = Some(e);
+            let backoff = Duration::from_millis(settings.probe.backoff_ms)
+                .saturating_mul(2u32.saturating_pow(health.reconnect_attempts - 1))
+                .min(MAX_RECONNECT_BACKOFF);
+            health.next_reconnect = Some(Instant::now() + backoff);
+        }
+    }
+    emit(&current_connection.health);
+    Ok(())
+}
+
+pub fn emit_connection_status(app_handle: &AppHandle, health: &LinkHealth) {
+    if let Err(e) = app_handle.emit("connection_status", health) {
+        eprintln!("Failed to emit connection status: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn connection_status(
+    connection_state: State<'_, Mutex<CurrentConnection>>,
+) -> Result<LinkHealth, AppError> {
+    let current_connection = connection_state.lock().await;
+    Ok(current_connection.health.clone())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::Transport;
+
+    #[tokio::test]
+    async fn cancelled_reconnect_leaves_link_disconnected() {
+        let simulated = Settings {
+            transport: Transport::Simulated,
+            ..Settings::default()
+        };
+        let mut connection = CurrentConnection::default();
+        connection.set_connection(ModbusClient::connect(&simulated).await.unwrap());
+        let connection_state = Mutex::new(connection);
+
+        // nothing listens on the port, the probe retries for seconds
+        let mut unreachable = Settings {
+            transport: Transport::Tcp,
+            ..Settings::default()
+        };
+        unreachable.network.port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        unreachable.probe.retries = 5;
+
+        let emitted = std::sync::Mutex::new(Vec::new());
+        let emit = |health: &LinkHealth| emitted.lock().unwrap().push(health.state);
+        let token = CancellationToken::new();
+        for _ in 1..FAILURES_BEFORE_RECONNECT {
+            supervise_failure(
+                &connection_state,
+                Some(unreachable.clone()),
+                AppError::NotConnected,
+                &token,
+                emit,
+            )
+            .await
+            .unwrap();
+        }
+
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel.cancel();
+        });
+        let reconnect = supervise_failure(
+            &connection_state,
+            Some(unreachable),
+            AppError::NotConnected,
+            &token,
+            emit,
+        );
+        tokio::time::timeout(Duration::from_secs(1), reconnect)
+            .await
+            .expect("reconnect not abandoned on cancel")
+            .unwrap();
+
+        let connection = connection_state.lock().await;
+        assert!(!connection.is_connected());
+        assert_eq!(connection.health.state, LinkState::Disconnected);
+        assert_eq!(
+            *emitted.lock().unwrap(),
+            [
+                LinkState::Degraded,
+                LinkState::Degraded,
+                LinkState::Reconnecting,
+                LinkState::Disconnected
+            ]
+        );
+    }
+}
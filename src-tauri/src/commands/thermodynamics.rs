@@ -0,0 +1,239 @@
+use super::activity::{ActivityModel, ActivityModelKind, Interaction};
+use super::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// Antoine equation constants: log10(P [mmHg]) = A - B / (C + T [°C])
+#[derive(Debug, Clone, Copy)]
+pub struct Antoine {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+impl Antoine {
+    pub fn vapor_pressure(&self, temperature: f64) -> f64 {
+        10.0f64.powf(self.a - self.b / (self.c + temperature))
+    }
+
+    pub fn saturation_temperature(&self, pressure: f64) -> f64 {
+        self.b / (self.a - pressure.log10()) - self.c
+    }
+}
+
+/// Pure component data. Molar volume in cm³/mol, `r` and `q` are the UNIQUAC
+/// volume and surface area parameters.
+#[derive(Debug, Clone, Copy)]
+pub struct Component {
+    pub name: &'static str,
+    pub antoine: Antoine,
+    pub molar_volume: f64,
+    pub r: f64,
+    pub q: f64,
+}
+
+pub const ETHANOL: Component = Component {
+    name: "Ethanol",
+    antoine: Antoine {
+        a: 8.12875,
+        b: 1660.8713,
+        c: 238.131,
+    },
+    molar_volume: 58.68,
+    r: 2.1055,
+    q: 1.972,
+};
+
+pub const METHANOL: Component = Component {
+    name: "Methanol",
+    antoine: Antoine {
+        a: 8.08097,
+        b: 1582.271,
+        c: 239.726,
+    },
+    molar_volume: 40.73,
+    r: 1.4311,
+    q: 1.432,
+};
+
+pub const ACETONE: Component = Component {
+    name: "Acetone",
+    antoine: Antoine {
+        a: 7.11714,
+        b: 1210.595,
+        c: 229.664,
+    },
+    molar_volume: 74.05,
+    r: 2.5735,
+    q: 2.336,
+};
+
+pub const WATER: Component = Component {
+    name: "Water",
+    antoine: Antoine {
+        a: 8.05573,
+        b: 1723.6425,
+        c: 233.08,
+    },
+    molar_volume: 18.07,
+    r: 0.92,
+    q: 1.40,
+};
+
+/// Binary mixture where component 1 is the light (more volatile) key
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryMixture {
+    pub light: Component,
+    pub heavy: Component,
+    pub activity: ActivityModel,
+}
+
+impl BinaryMixture {
+    pub fn gammas(&self, x_1: f64, temp: f64) -> (f64, f64) {
+        self.activity.gammas(&self.light, &self.heavy, x_1, temp)
+    }
+
+    /// Boiling points of the pure components, (light, heavy), at the given pressure
+    pub fn boiling_range(&self, pressure: f64) -> (f64, f64) {
+        (
+            self.light.antoine.saturation_temperature(pressure),
+            self.heavy.antoine.saturation_temperature(pressure),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::enum_variant_names)]
+pub enum Mixture {
+    #[default]
+    EthanolWater,
+    MethanolWater,
+    AcetoneWater,
+}
+
+impl Mixture {
+    pub const ALL: [Mixture; 3] = [
+        Mixture::EthanolWater,
+        Mixture::MethanolWater,
+        Mixture::AcetoneWater,
+    ];
+
+    pub fn model(&self, kind: ActivityModelKind) -> BinaryMixture {
+        let (light, heavy) = self.components();
+        BinaryMixture {
+            light,
+            heavy,
+            activity: self.activity(kind),
+        }
+    }
+
+    pub fn components(&self) -> (Component, Component) {
+        match self {
+            Mixture::EthanolWater => (ETHANOL, WATER),
+            Mixture::MethanolWater => (METHANOL, WATER),
+            Mixture::AcetoneWater => (ACETONE, WATER),
+        }
+    }
+
+    /// Built-in activity model parameters for the mixture. Temperature dependent
+    /// parameters reproduce the Van Laar infinite dilution coefficients near the
+    /// middle of the boiling range.
+    pub fn activity(&self, kind: ActivityModelKind) -> ActivityModel {
+        match (self, kind) {
+            (Mixture::EthanolWater, ActivityModelKind::VanLaar) => ActivityModel::VanLaar {
+                a_12: 1.6798,
+                a_21: 0.9227,
+            },
+            (Mixture::EthanolWater, ActivityModelKind::Margules2) => ActivityModel::Margules2 {
+                a: Interaction::new(0.0, 471.47),
+            },
+            (Mixture::EthanolWater, ActivityModelKind::Margules3) => ActivityModel::Margules3 {
+                a_12: Interaction::new(0.0, 608.63),
+                a_21: Interaction::new(0.0, 334.32),
+            },
+            (Mixture::EthanolWater, ActivityModelKind::Wilson) => ActivityModel::Wilson {
+                lambda_12: 271.10,
+                lambda_21: 944.56,
+            },
+            (Mixture::EthanolWater, ActivityModelKind::Nrtl) => ActivityModel::Nrtl {
+                alpha: 0.3,
+                tau_12: Interaction::new(0.0, -47.22),
+                tau_21: Interaction::new(0.0, 657.73),
+            },
+            (Mixture::EthanolWater, ActivityModelKind::Uniquac) => ActivityModel::Uniquac {
+                tau_12: Interaction::new(0.0, -24.98),
+                tau_21: Interaction::new(0.0, -116.60),
+            },
+            (Mixture::MethanolWater, ActivityModelKind::VanLaar) => ActivityModel::VanLaar {
+                a_12: 0.8041,
+                a_21: 0.5619,
+            },
+            (Mixture::MethanolWater, ActivityModelKind::Margules2) => ActivityModel::Margules2 {
+                a: Interaction::new(0.0, 242.75),
+            },
+            (Mixture::MethanolWater, ActivityModelKind::Margules3) => ActivityModel::Margules3 {
+                a_12: Interaction::new(0.0, 285.79),
+                a_21: Interaction::new(0.0, 199.71),
+            },
+            (Mixture::MethanolWater, ActivityModelKind::Wilson) => ActivityModel::Wilson {
+                lambda_12: -17.98,
+                lambda_21: 586.02,
+            },
+            (Mixture::MethanolWater, ActivityModelKind::Nrtl) => ActivityModel::Nrtl {
+                alpha: 0.3,
+                tau_12: Interaction::new(0.0, -60.46),
+                tau_21: Interaction::new(0.0, 349.42),
+            },
+            (Mixture::MethanolWater, ActivityModelKind::Uniquac) => ActivityModel::Uniquac {
+                tau_12: Interaction::new(0.0, 117.25),
+                tau_21: Interaction::new(0.0, -186.22),
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::VanLaar) => ActivityModel::VanLaar {
+                a_12: 2.0500,
+                a_21: 1.5022,
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::Margules2) => ActivityModel::Margules2 {
+                a: Interaction::new(0.0, 623.76),
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::Margules3) => ActivityModel::Margules3 {
+                a_12: Interaction::new(0.0, 719.96),
+                a_21: Interaction::new(0.0, 527.57),
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::Wilson) => ActivityModel::Wilson {
+                lambda_12: 89.16,
+                lambda_21: 1484.76,
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::Nrtl) => ActivityModel::Nrtl {
+                alpha: 0.3,
+                tau_12: Interaction::new(0.0, 178.29),
+                tau_21: Interaction::new(0.0, 566.86),
+            },
+            (Mixture::AcetoneWater, ActivityModelKind::Uniquac) => ActivityModel::Uniquac {
+                tau_12: Interaction::new(0.0, -373.26),
+                tau_21: Interaction::new(0.0, 86.67),
+            },
+        }
+    }
+
+    pub fn name(&self) -> String {
+        let (light, heavy) = self.components();
+        format!("{} - {}", light.name, heavy.name)
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct MixtureInfo {
+    id: Mixture,
+    name: String,
+}
+
+#[tauri::command]
+pub async fn available_mixtures() -> Result<Vec<MixtureInfo>, AppError> {
+    Ok(Mixture::ALL
+        .iter()
+        .map(|mixture| MixtureInfo {
+            id: *mixture,
+            name: mixture.name(),
+        })
+        .collect())
+}
@@ -1,146 +1,318 @@
-use super::data_manager::{ColumnEntry, DataSource, MeasurementHistory};
-use calamine::{open_workbook, DataType, Reader, Xlsx};
-use rust_xlsxwriter::{Workbook, XlsxError};
+use super::csv_file::{self, CsvOptions, NumberedRow};
+use super::data_manager::{ColumnEntry, DataSource};
+use super::error::AppError;
+use super::history::MeasurementHistory;
+use super::playback::Playback;
+use super::run_log::{run_entries, RunLog};
+use super::settings::PressureSettings;
+use super::settings::SettingsState;
+use super::xlsx_file::{self, ExportMetadata};
+use calamine::{Data, DataType};
+use serde::Serialize;
 use std::sync::Arc;
 use tauri::{AppHandle, State};
 use tauri_plugin_dialog::DialogExt;
 use tokio::sync::Mutex;
 
+/// Outcome of an import. Row numbers are the ones shown by the spreadsheet or
+/// the line numbers of a CSV file.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Sheet the data was read from, none for CSV files
+    pub sheet: Option<String>,
+    pub rows_imported: usize,
+    pub rows_rejected: Vec<RejectedRow>,
+    pub plate_count: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedRow {
+    pub row: usize,
+    pub reason: String,
+}
+
+/// Imports an xlsx or CSV file, chosen by extension, as playback data
 #[tauri::command]
 pub async fn import_data(
     data_source_state: State<'_, Mutex<DataSource>>,
+    history_state: State<'_, Mutex<MeasurementHistory>>,
     path: String,
-) -> Result<(), ()> {
-    let mut imported_data: Vec<Arc<ColumnEntry>> = Vec::new();
-    let mut workbook: Xlsx<_> = open_workbook(path).unwrap();
-    let worksheet_name = workbook
-        .sheet_names()
+    csv: Option<CsvOptions>,
+) -> Result<ImportReport, AppError> {
+    let (sheet, rows) = if csv_file::is_csv(&path) {
+        (None, csv_file::read_rows(&path, &csv.unwrap_or_default())?)
+    } else {
+        let (sheet, rows) = xlsx_file::read_rows(&path)?;
+        (Some(sheet), rows)
+    };
+    let context = match &sheet {
+        Some(sheet) => format!("{} sheet {}", path, sheet),
+        None => path.clone(),
+    };
+
+    let (entries, rows_rejected, plate_count) = parse_rows(&context, &rows)?;
+    let report = ImportReport {
+        sheet,
+        rows_imported: entries.len(),
+        rows_rejected,
+        plate_count,
+    };
+    println!(
+        "Imported {} rows from {}, {} rejected",
+        report.rows_imported,
+        context,
+        report.rows_rejected.len()
+    );
+
+    {
+        let mut ds = data_source_state.lock().await;
+        *ds = DataSource::Playback(Playback::new(entries));
+    }
+    // the buckets assume time moves forward, start over with the file
+    history_state.lock().await.clear();
+    Ok(report)
+}
+
+/// Column positions of an imported file. The timestamp is always the first
+/// column and the compositions follow the temperatures.
+struct Layout {
+    /// Milliseconds per unit of the timestamp column
+    timestamp_scale: f64,
+    pressure: Option<usize>,
+    first_temperature: usize,
+    plate_count: usize,
+}
+
+impl Layout {
+    /// Locates the columns by header name, ignoring units and extra columns such
+    /// as the formatted time of xlsx exports. Timestamps are in seconds unless
+    /// the header says `[ms]`. Without a header the file is read as timestamp,
+    /// temperatures and compositions.
+    fn detect(context: &str, header: &[Data]) -> Result<(Self, bool), AppError> {
+        let has_header = header
+            .first()
+            .is_some_and(|cell| cell.get_string().is_some());
+        if !has_header {
+            let layout = Layout {
+                timestamp_scale: 1000.0,
+                pressure: None,
+                first_temperature: 1,
+                plate_count: header.len().saturating_sub(1) / 2,
+            };
+            return Ok((layout, false));
+        }
+
+        let column_named = |prefix: &str| {
+            header.iter().position(|cell| {
+                cell.get_string()
+                    .is_some_and(|name| name.starts_with(prefix))
+            })
+        };
+        let Some(first_temperature) = column_named("Temperature") else {
+            return Err(AppError::parse(context, "No temperature columns found"));
+        };
+        let plate_count = header[first_temperature..]
+            .iter()
+            .take_while(|cell| {
+                cell.get_string()
+                    .is_some_and(|name| name.starts_with("Temperature"))
+            })
+            .count();
+        // files exported before millisecond timestamps are in seconds
+        let in_millis = header
+            .first()
+            .and_then(|cell| cell.get_string())
+            .is_some_and(|name| name.contains("[ms]"));
+        let layout = Layout {
+            timestamp_scale: if in_millis { 1.0 } else { 1000.0 },
+            // files exported before the pressure column was added were computed at the default pressure
+            pressure: column_named("Pressure"),
+            first_temperature,
+            plate_count,
+        };
+        Ok((layout, true))
+    }
+}
+
+/// Detects the column layout from the header and parses every data row,
+/// returning the valid entries, the rejected rows and the plate count
+fn parse_rows(
+    context: &str,
+    rows: &[NumberedRow],
+) -> Result<(Vec<ColumnEntry>, Vec<RejectedRow>, usize), AppError> {
+    let header = rows
         .first()
-        .cloned()
-        .ok_or("Not valid sheets")
-        .unwrap();
-    let range = workbook
-        .worksheet_range(&worksheet_name)
-        .map_err(|_| "Can't read sheet")
-        .unwrap();
-    let total_rows = range.rows().count().saturating_sub(1);
-
-    for (index, row) in range.rows().skip(1).enumerate() {
-        println!("Processing row {}", index);
-        let percentage_complete = (index as f64 + 1.0) / total_rows as f64 * 100.0;
-        if row.is_empty() {
+        .map(|(_, row)| row.as_slice())
+        .unwrap_or_default();
+    let (layout, has_header) = Layout::detect(context, header)?;
+    if layout.plate_count == 0 {
+        return Err(AppError::parse(context, "No temperature columns found"));
+    }
+
+    let mut entries: Vec<ColumnEntry> = Vec::new();
+    let mut rows_rejected = Vec::new();
+    let skip = if has_header { 1 } else { 0 };
+    for (row_number, row) in rows.iter().skip(skip) {
+        if row.iter().all(|cell| cell.is_empty()) {
             continue;
         }
-        if row.len() < 3 {
-            continue;
+        match parse_row(row, &layout) {
+            Ok(entry) => entries.push(entry),
+            Err(reason) => rows_rejected.push(RejectedRow {
+                row: *row_number,
+                reason,
+            }),
         }
+    }
 
-        let timestamp = if let Some(ts) = row.get(0).and_then(|cell| cell.as_f64()) {
-            ts as u64
-        } else {
-            continue;
-        };
+    if entries.is_empty() {
+        let reason = rows_rejected
+            .first()
+            .map(|rejected| format!("row {}: {}", rejected.row, rejected.reason))
+            .unwrap_or("the file has no data rows".into());
+        return Err(AppError::parse(
+            context,
+            format!("No valid rows, {}", reason),
+        ));
+    }
+    Ok((entries, rows_rejected, layout.plate_count))
+}
 
-        let values_lenght = (row.len() - 1) / 2;
+/// Reads one data row: the timestamp, the pressure when present, the
+/// temperature of every plate and then the composition of every plate
+fn parse_row(row: &[Data], layout: &Layout) -> Result<ColumnEntry, String> {
+    let number = |column: usize, name: &str| {
+        row.get(column)
+            .and_then(|cell| cell.as_f64())
+            .filter(|value| value.is_finite())
+            .ok_or(format!("{} is missing or not a number", name))
+    };
 
-        let temperatures: Vec<f64> = row
-            .iter()
-            .skip(1)
-            .take(values_lenght)
-            .filter_map(|cell| cell.as_f64())
-            .collect();
+    let timestamp = number(0, "Timestamp")?;
+    if timestamp < 0.0 {
+        return Err("Timestamp is negative".into());
+    }
+    let pressure = match layout.pressure {
+        Some(column) => number(column, "Pressure")?,
+        None => PressureSettings::default().value,
+    };
+    if pressure <= 0.0 {
+        return Err(format!("Pressure {} is not positive", pressure));
+    }
 
-        let compositions: Vec<f64> = row
-            .iter()
-            .skip(1 + values_lenght)
-            .filter_map(|cell| cell.as_f64())
-            .collect();
-
-        println!("Timestamp {}", timestamp);
-        println!("Temperatures {:?}", temperatures);
-        println!("Compositions {:?}", compositions);
-
-        imported_data.push(Arc::new(ColumnEntry {
-            timestamp,
-            temperatures,
-            compositions,
-            percentage_complete,
-        }));
+    let plate_count = layout.plate_count;
+    let mut temperatures = Vec::with_capacity(plate_count);
+    for plate in 0..plate_count {
+        temperatures.push(number(
+            layout.first_temperature + plate,
+            &format!("Temperature {}", plate + 1),
+        )?);
     }
-    {
-        let mut ds = data_source_state.lock().await;
-        *ds = DataSource::Playback {
-            index: 0,
-            data: imported_data,
-        };
+
+    // entries out of the boiling range are recorded without compositions
+    let first_composition = layout.first_temperature + plate_count;
+    let no_compositions = (first_composition..first_composition + plate_count)
+        .all(|column| row.get(column).is_none_or(|cell| cell.is_empty()));
+    let mut compositions = Vec::with_capacity(plate_count);
+    if !no_compositions {
+        for plate in 0..plate_count {
+            let composition = number(
+                first_composition + plate,
+                &format!("Composition {}", plate + 1),
+            )?;
+            if !(0.0..=1.0).contains(&composition) {
+                return Err(format!(
+                    "Composition {} is out of range: {}",
+                    plate + 1,
+                    composition
+                ));
+            }
+            compositions.push(composition);
+        }
     }
-    Ok(())
+
+    Ok(ColumnEntry {
+        timestamp: (timestamp * layout.timestamp_scale) as u64,
+        pressure,
+        temperatures,
+        compositions,
+        vapor_compositions: Vec::new(),
+        percentage_complete: 0.0,
+    })
 }
 
+/// Exports the session as xlsx or CSV, chosen by extension: the whole playback
+/// file, or else the latest logged run. The history retains only the last hours
+/// and is exported when neither is available. The operator and the UTC offset in
+/// minutes of the local time only apply to xlsx.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn export_data(
+    app_handle: AppHandle,
     column_data_state: State<'_, Mutex<MeasurementHistory>>,
+    data_source_state: State<'_, Mutex<DataSource>>,
+    run_log_state: State<'_, Mutex<RunLog>>,
+    settings_state: State<'_, Mutex<SettingsState>>,
     path: String,
-) -> Result<(), String> {
-    println!("Export data to excel...");
-    let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
-
-    let column_data = {
-        let data_column = column_data_state.lock().await;
-        data_column.history.clone()
+    csv: Option<CsvOptions>,
+    operator: Option<String>,
+    utc_offset: Option<i32>,
+) -> Result<(), AppError> {
+    let playback = match &*data_source_state.lock().await {
+        DataSource::Playback(playback) if !playback.entries().is_empty() => {
+            Some(playback.entries().to_vec())
+        }
+        _ => None,
     };
-
-    println!("Writing headers...");
-
-    let Some(first) = column_data.first() else {
-        return Err("No current data".into());
+    let run = {
+        let run_log = run_log_state.lock().await;
+        run_log.latest_id().map(String::from)
+    };
+    let current_settings = {
+        let current_settings = settings_state.lock().await;
+        current_settings.settings.clone()
+    };
+    // a logged run is described by the settings it was acquired with
+    let (column_data, settings) = match (playback, run) {
+        (Some(entries), _) => (entries, current_settings),
+        (None, Some(id)) => run_entries(&app_handle, &id).await?,
+        (None, None) => {
+            let data_column = column_data_state.lock().await;
+            (data_column.entries(), current_settings)
+        }
+    };
+    let metadata = ExportMetadata {
+        operator: operator.as_deref().filter(|operator| !operator.is_empty()),
+        settings: settings.as_ref(),
+        utc_offset: utc_offset.unwrap_or_default(),
     };
+    write_history(&path, &column_data, &metadata, &csv.unwrap_or_default())
+}
 
-    // write headers
-    worksheet
-        .write(0, 0, "Timestamp")
-        .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-    let num_values = first.temperatures.len();
-    for i in 0..num_values {
-        worksheet
-            .write(0, (i + 1) as u16, format!("Temperature {}", i + 1))
-            .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-        worksheet
-            .write(
-                0,
-                (num_values + i + 1) as u16,
-                format!("Composition {}", i + 1),
-            )
-            .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-    }
+/// Writes a history as xlsx or CSV, chosen by extension
+pub fn write_history(
+    path: &str,
+    history: &[Arc<ColumnEntry>],
+    metadata: &ExportMetadata,
+    csv: &CsvOptions,
+) -> Result<(), AppError> {
+    let Some(first) = history.first() else {
+        return Err(AppError::no_data("No current data"));
+    };
 
-    // write data
-    println!("Writing data");
-    for (row, value) in column_data.iter().enumerate() {
-        let row = (row + 1) as u32;
-        worksheet
-            .write(row, 0, value.timestamp)
-            .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-
-        for (i, &temp) in value.temperatures.iter().enumerate() {
-            worksheet
-                .write(row, (i + 1) as u16, temp)
-                .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-        }
-        for (i, &comp) in value.compositions.iter().enumerate() {
-            worksheet
-                .write(row, (num_values + i + 1) as u16, comp)
-                .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
-        }
+    if csv_file::is_csv(path) {
+        println!("Export data to csv...");
+        let header = ColumnEntry::headers(first.temperatures.len());
+        csv_file::write_rows(path, csv, &header, history)?;
+        println!("CSV saved");
+        return Ok(());
     }
 
-    println!("Saving excel...");
-    workbook
-        .save(path)
-        .map_err(|e: XlsxError| format!("Xlsx error: {}", e))?;
+    println!("Export data to excel...");
+    xlsx_file::write(path, history, metadata)?;
     println!("Excel saved");
-
     Ok(())
 }
 
@@ -158,7 +330,7 @@ pub async fn file_path(app: AppHandle) -> String {
     let file_path = app
         .dialog()
         .file()
-        .add_filter("My filert", &["xlsx"])
+        .add_filter("Data files", &["xlsx", "csv"])
         .blocking_pick_file();
     if let Some(file_path) = file_path {
         return file_path.to_string();
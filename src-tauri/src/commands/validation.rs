@@ -0,0 +1,306 @@
+use super::error::{AppError, FieldError};
+use super::settings::{AcquisitionSettings, ProbeFunction, Settings, Transport};
+use std::fmt::Display;
+
+/// Registers a single Modbus read can return
+const MAX_READ_REGISTERS: u16 = 125;
+/// Coils or discrete inputs a single Modbus read can return
+const MAX_READ_BITS: u16 = 2000;
+const MIN_TIMEOUT_MS: u64 = 100;
+const MAX_TIMEOUT_MS: u64 = 10_000;
+const MAX_RETRIES: u32 = 10;
+const MAX_BACKOFF_MS: u64 = 60_000;
+
+/// Register block read for a channel, checked for overlaps with the others
+struct Channel {
+    field: String,
+    unit_id: u8,
+    start: u32,
+    end: u32,
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn check(&mut self, valid: bool, field: impl Into<String>, message: impl Into<String>) {
+        if !valid {
+            self.error(field, message);
+        }
+    }
+
+    fn range<T: PartialOrd + Display + Copy>(
+        &mut self,
+        field: impl Into<String>,
+        value: T,
+        min: T,
+        max: T,
+    ) {
+        if !(min..=max).contains(&value) {
+            self.error(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    fn at_least<T: PartialOrd + Display + Copy>(
+        &mut self,
+        field: impl Into<String>,
+        value: T,
+        min: T,
+    ) {
+        if !(min..).contains(&value) {
+            self.error(field, format!("must be at least {}", min));
+        }
+    }
+
+    fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::SettingsRejected {
+                errors: self.errors,
+            })
+        }
+    }
+}
+
+/// Checks every field of `settings` and rejects them with the list of problems.
+/// The serial port is only checked against `ports` when given, as the adapter
+/// may well be unplugged while the settings are loaded.
+pub fn validate_settings(settings: &Settings, ports: Option<&[String]>) -> Result<(), AppError> {
+    let mut v = Validator::default();
+
+    match settings.transport {
+        Transport::RtuSerial => {
+            v.check(settings.baudrate > 0, "baudrate", "must be greater than 0");
+            v.check(
+                matches!(settings.serial.data_bits, 7 | 8),
+                "serial.dataBits",
+                "must be 7 or 8",
+            );
+            v.check(
+                matches!(settings.serial.stop_bits, 1 | 2),
+                "serial.stopBits",
+                "must be 1 or 2",
+            );
+            if let Some(ports) = ports {
+                if settings.usb_port.is_empty() {
+                    v.error("usbPort", "is required");
+                } else if !ports.contains(&settings.usb_port) {
+                    v.error("usbPort", format!("{} is not connected", settings.usb_port));
+                }
+            }
+        }
+        Transport::Tcp | Transport::RtuOverTcp => {
+            v.check(
+                !settings.network.host.trim().is_empty(),
+                "network.host",
+                "is required",
+            );
+            v.check(
+                settings.network.port > 0,
+                "network.port",
+                "must be between 1 and 65535",
+            );
+        }
+        Transport::Simulated => {}
+    }
+
+    v.range("timeout", settings.timeout, MIN_TIMEOUT_MS, MAX_TIMEOUT_MS);
+    v.at_least("numberPlates", settings.number_plates, 2);
+
+    v.range(
+        "acquisition.samplePeriodMs",
+        settings.acquisition.sample_period_ms,
+        AcquisitionSettings::MIN_SAMPLE_PERIOD_MS,
+        AcquisitionSettings::MAX_SAMPLE_PERIOD_MS,
+    );
+    let history = &settings.history;
+    v.at_least(
+        "history.rawRetentionMinutes",
+        history.raw_retention_minutes,
+        1,
+    );
+    v.at_least("history.bucketSeconds", history.bucket_seconds, 1);
+    v.at_least(
+        "history.bucketRetentionHours",
+        history.bucket_retention_hours,
+        1,
+    );
+
+    let probe = &settings.probe;
+    let max_probe_count = match probe.function {
+        ProbeFunction::Coils | ProbeFunction::DiscreteInputs => MAX_READ_BITS,
+        ProbeFunction::HoldingRegisters | ProbeFunction::InputRegisters => MAX_READ_REGISTERS,
+    };
+    v.range("probe.count", probe.count, 1, max_probe_count);
+    v.range("probe.retries", probe.retries, 0, MAX_RETRIES);
+    v.range(
+        "probe.timeoutMs",
+        probe.timeout_ms,
+        MIN_TIMEOUT_MS,
+        MAX_TIMEOUT_MS,
+    );
+    v.range("probe.backoffMs", probe.backoff_ms, 0, MAX_BACKOFF_MS);
+
+    let channels = validate_sensors(&mut v, settings);
+    validate_overlaps(&mut v, &channels);
+    validate_simulation(&mut v, settings);
+    v.finish()
+}
+
+/// Checks a pressure in mmHg given to a command in place of the configured one
+pub fn validate_pressure(pressure: Option<f64>) -> Result<(), AppError> {
+    let mut v = Validator::default();
+    if let Some(pressure) = pressure {
+        check_pressure(&mut v, pressure);
+    }
+    v.finish()
+}
+
+fn check_pressure(v: &mut Validator, pressure: f64) {
+    v.check(
+        pressure.is_finite() && pressure > 0.0,
+        "pressure.value",
+        "must be greater than 0",
+    );
+}
+
+/// Checks the temperature sensors and the pressure register, returning the
+/// register blocks they read
+fn validate_sensors(v: &mut Validator, settings: &Settings) -> Vec<Channel> {
+    let mut channels = Vec::new();
+    let explicit = !settings.plate_sensors.is_empty();
+    let mut assigned: Vec<Option<usize>> = vec![None; settings.number_plates.max(2)];
+
+    for (i, sensor) in settings.temperature_sensors().iter().enumerate() {
+        let address_field = match (explicit, i) {
+            (true, _) => format!("plateSensors[{}].address", i),
+            (false, 0) => "temperatureAddress.bottom".to_string(),
+            (false, _) => "temperatureAddress.top".to_string(),
+        };
+        channels.push(Channel {
+            field: address_field,
+            unit_id: sensor.unit_id.unwrap_or(settings.unit_id),
+            start: sensor.address as u32,
+            end: sensor.address as u32 + sensor.format.data_type.register_count() as u32,
+        });
+        if !explicit {
+            continue;
+        }
+
+        let field = |name: &str| format!("plateSensors[{}].{}", i, name);
+        match assigned.get_mut(sensor.plate) {
+            None => v.error(
+                field("plate"),
+                format!(
+                    "plate {} is outside the column of {} plates",
+                    sensor.plate, settings.number_plates
+                ),
+            ),
+            Some(Some(other)) => v.error(
+                field("plate"),
+                format!(
+                    "plate {} already has sensor plateSensors[{}]",
+                    sensor.plate, other
+                ),
+            ),
+            Some(slot) => *slot = Some(i),
+        }
+        v.check(
+            sensor.scale.is_finite() && sensor.scale != 0.0,
+            field("scale"),
+            "must be a non-zero number",
+        );
+        v.check(
+            sensor.offset.is_finite(),
+            field("offset"),
+            "must be a number",
+        );
+    }
+
+    let pressure = &settings.pressure;
+    match pressure.address {
+        Some(address) => {
+            channels.push(Channel {
+                field: "pressure.address".to_string(),
+                unit_id: settings.unit_id,
+                start: address as u32,
+                end: address as u32 + pressure.format.data_type.register_count() as u32,
+            });
+            v.check(
+                pressure.scale.is_finite() && pressure.scale != 0.0,
+                "pressure.scale",
+                "must be a non-zero number",
+            );
+            v.check(
+                pressure.offset.is_finite(),
+                "pressure.offset",
+                "must be a number",
+            );
+        }
+        None => check_pressure(v, pressure.value),
+    }
+    channels
+}
+
+/// Every channel must read its own registers of its unit
+fn validate_overlaps(v: &mut Validator, channels: &[Channel]) {
+    for (i, channel) in channels.iter().enumerate() {
+        if channel.end > u16::MAX as u32 + 1 {
+            v.error(&channel.field, "runs past the last register address");
+        }
+        if let Some(other) = channels[..i].iter().find(|other| {
+            other.unit_id == channel.unit_id
+                && other.start < channel.end
+                && channel.start < other.end
+        }) {
+            v.error(
+                &channel.field,
+                format!("overlaps the registers of {}", other.field),
+            );
+        }
+    }
+}
+
+fn validate_simulation(v: &mut Validator, settings: &Settings) {
+    let simulation = &settings.simulation;
+    v.check(
+        simulation.ambient_temperature.is_finite(),
+        "simulation.ambientTemperature",
+        "must be a number",
+    );
+    v.at_least("simulation.heatUpMinutes", simulation.heat_up_minutes, 0.0);
+    v.range(
+        "simulation.feedComposition",
+        simulation.feed_composition,
+        0.0,
+        1.0,
+    );
+    v.at_least("simulation.refluxMin", simulation.reflux_min, 0.0);
+    v.at_least(
+        "simulation.refluxMax",
+        simulation.reflux_max,
+        simulation.reflux_min,
+    );
+    v.at_least(
+        "simulation.refluxChangeMinutes",
+        simulation.reflux_change_minutes,
+        0.0,
+    );
+    v.at_least("simulation.noise", simulation.noise, 0.0);
+    v.range("simulation.faultRate", simulation.fault_rate, 0.0, 1.0);
+    v.check(
+        simulation.time_scale.is_finite() && simulation.time_scale > 0.0,
+        "simulation.timeScale",
+        "must be greater than 0",
+    );
+}
@@ -0,0 +1,333 @@
+use super::csv_file::NumberedRow;
+use super::data_manager::ColumnEntry;
+use super::error::AppError;
+use super::modbus_serial::ModbusClient;
+use super::settings::Settings;
+use calamine::{open_workbook, DataType, Reader, Xlsx};
+use rust_xlsxwriter::{
+    Chart, ChartType, Format, FormatAlign, FormatBorder, IntoExcelData, Workbook, Worksheet,
+    XlsxError,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DATA_SHEET: &str = "Data";
+const METADATA_SHEET: &str = "Metadata";
+const CHARTS_SHEET: &str = "Charts";
+
+const DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+const DATETIME_MS_FORMAT: &str = "yyyy-mm-dd hh:mm:ss.000";
+const TIME_FORMAT: &str = "hh:mm:ss";
+const DURATION_FORMAT: &str = "[h]:mm:ss";
+
+/// Excel serial date of 1970-01-01
+const UNIX_EPOCH_SERIAL: f64 = 25569.0;
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// Run information written to the metadata sheet
+pub struct ExportMetadata<'a> {
+    pub operator: Option<&'a str>,
+    pub settings: Option<&'a Settings>,
+    /// Offset of local time from UTC in minutes, Excel dates have no time zone
+    pub utc_offset: i32,
+}
+
+/// Reads the sheet written by export_data, otherwise the first one, with the
+/// spreadsheet row number of every row
+pub fn read_rows(path: &str) -> Result<(String, Vec<NumberedRow>), AppError> {
+    let mut workbook: Xlsx<_> = open_workbook(path).map_err(|e| AppError::file(path, e))?;
+
+    let mut chosen = None;
+    for name in workbook.sheet_names() {
+        let Ok(range) = workbook.worksheet_range(&name) else {
+            continue;
+        };
+        let is_data = range
+            .rows()
+            .next()
+            .and_then(|header| header.first())
+            .and_then(|cell| cell.get_string())
+            .is_some_and(|name| name.starts_with("Timestamp"));
+        if is_data || chosen.is_none() {
+            chosen = Some((name, range));
+        }
+        if is_data {
+            break;
+        }
+    }
+    let (sheet, range) = chosen.ok_or(AppError::parse(path, "Workbook has no readable sheets"))?;
+    let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+
+    let rows = range
+        .rows()
+        .enumerate()
+        .map(|(index, row)| (first_row + index + 1, row.to_vec()))
+        .collect();
+    Ok((sheet, rows))
+}
+
+/// Writes the history to a data sheet with formatted times and units, a sheet
+/// with the run metadata and a sheet with temperature and composition charts
+pub fn write(
+    path: &str,
+    history: &[Arc<ColumnEntry>],
+    metadata: &ExportMetadata,
+) -> Result<(), AppError> {
+    build_workbook(history, metadata)
+        .and_then(|mut workbook| workbook.save(path))
+        .map_err(|e| AppError::file(path, e))
+}
+
+fn build_workbook(
+    history: &[Arc<ColumnEntry>],
+    metadata: &ExportMetadata,
+) -> Result<Workbook, XlsxError> {
+    let mut workbook = Workbook::new();
+    let plates = history
+        .first()
+        .map(|entry| entry.temperatures.len())
+        .unwrap_or_default();
+
+    write_data(
+        workbook.add_worksheet(),
+        history,
+        plates,
+        metadata.utc_offset,
+    )?;
+    write_metadata(workbook.add_worksheet(), history, plates, metadata)?;
+    write_charts(workbook.add_worksheet(), history.len(), plates)?;
+    Ok(workbook)
+}
+
+/// Data sheet laid out as the CSV export, with the local time after the timestamp
+fn write_data(
+    worksheet: &mut Worksheet,
+    history: &[Arc<ColumnEntry>],
+    plates: usize,
+    utc_offset: i32,
+) -> Result<(), XlsxError> {
+    worksheet.set_name(DATA_SHEET)?;
+    let header_format = header_format();
+    let timestamp_format = Format::new().set_num_format("0");
+    let time_format = Format::new().set_num_format(DATETIME_MS_FORMAT);
+    let pressure_format = Format::new().set_num_format("0.0");
+    let temperature_format = Format::new().set_num_format("0.00");
+    let composition_format = Format::new().set_num_format("0.0000");
+
+    let mut header = ColumnEntry::headers(plates);
+    header.insert(1, "Time".to_string());
+    for (column, name) in header.iter().enumerate() {
+        worksheet.write_with_format(0, column as u16, name, &header_format)?;
+    }
+    worksheet.set_column_width(0, 14)?;
+    worksheet.set_column_width(1, 24)?;
+    for column in 2..header.len() {
+        worksheet.set_column_width(column as u16, 16)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    let first_temperature = 3;
+    let first_composition = first_temperature + plates;
+    for (row, entry) in history.iter().enumerate() {
+        let row = (row + 1) as u32;
+        worksheet.write_with_format(row, 0, entry.timestamp, &timestamp_format)?;
+        worksheet.write_with_format(
+            row,
+            1,
+            local_time(entry.timestamp, utc_offset),
+            &time_format,
+        )?;
+        worksheet.write_with_format(row, 2, entry.pressure, &pressure_format)?;
+        for (i, &temperature) in entry.temperatures.iter().enumerate() {
+            worksheet.write_with_format(
+                row,
+                (first_temperature + i) as u16,
+                temperature,
+                &temperature_format,
+            )?;
+        }
+        for (i, &composition) in entry.compositions.iter().enumerate() {
+            worksheet.write_with_format(
+                row,
+                (first_composition + i) as u16,
+                composition,
+                &composition_format,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_metadata(
+    worksheet: &mut Worksheet,
+    history: &[Arc<ColumnEntry>],
+    plates: usize,
+    metadata: &ExportMetadata,
+) -> Result<(), XlsxError> {
+    worksheet.set_name(METADATA_SHEET)?;
+    let header_format = header_format();
+    let text = Format::new().set_align(FormatAlign::Left);
+    let datetime = text.clone().set_num_format(DATETIME_FORMAT);
+    let duration = text.clone().set_num_format(DURATION_FORMAT);
+    let pressure = text.clone().set_num_format("0.0");
+    let local = |timestamp| local_time(timestamp, metadata.utc_offset);
+    worksheet.set_column_width(0, 28)?;
+    worksheet.set_column_width(1, 40)?;
+
+    worksheet.write_with_format(0, 0, "Run", &header_format)?;
+    worksheet.write_with_format(0, 1, "", &header_format)?;
+    let mut row = 1;
+    let operator = metadata.operator.unwrap_or_default();
+    write_field(worksheet, &mut row, "Operator", operator, &text)?;
+    if let (Some(first), Some(last)) = (history.first(), history.last()) {
+        let elapsed = last.timestamp.saturating_sub(first.timestamp) as f64 / MS_PER_DAY;
+        write_field(
+            worksheet,
+            &mut row,
+            "Start",
+            local(first.timestamp),
+            &datetime,
+        )?;
+        write_field(worksheet, &mut row, "End", local(last.timestamp), &datetime)?;
+        write_field(worksheet, &mut row, "Duration", elapsed, &duration)?;
+
+        let mean = history.iter().map(|entry| entry.pressure).sum::<f64>() / history.len() as f64;
+        write_field(worksheet, &mut row, "Mean pressure [mmHg]", mean, &pressure)?;
+    }
+    write_field(worksheet, &mut row, "Samples", history.len() as u32, &text)?;
+    write_field(worksheet, &mut row, "Plates", plates as u32, &text)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    write_field(worksheet, &mut row, "Exported", local(now), &datetime)?;
+
+    let Some(settings) = metadata.settings else {
+        return Ok(());
+    };
+    let pressure_source = match settings.pressure.address {
+        Some(address) => format!("Measured, register {}", address),
+        None => format!("Fixed, {} mmHg", settings.pressure.value),
+    };
+    let connection = ModbusClient::describe(settings);
+    write_field(
+        worksheet,
+        &mut row,
+        "Mixture",
+        settings.mixture.name(),
+        &text,
+    )?;
+    write_field(worksheet, &mut row, "Pressure", pressure_source, &text)?;
+    write_field(
+        worksheet,
+        &mut row,
+        "Activity model",
+        settings.activity_model.name(),
+        &text,
+    )?;
+    write_field(worksheet, &mut row, "Connection", connection, &text)?;
+
+    // full snapshot of the settings the run was acquired with
+    row += 1;
+    worksheet.write_with_format(row, 0, "Settings", &header_format)?;
+    worksheet.write_with_format(row, 1, "", &header_format)?;
+    row += 1;
+    let mut fields = Vec::new();
+    flatten_settings(
+        "",
+        &serde_json::to_value(settings).unwrap_or_default(),
+        &mut fields,
+    );
+    for (key, value) in fields {
+        write_field(worksheet, &mut row, &key, value, &text)?;
+    }
+    Ok(())
+}
+
+fn write_field<T: IntoExcelData>(
+    worksheet: &mut Worksheet,
+    row: &mut u32,
+    name: &str,
+    value: T,
+    format: &Format,
+) -> Result<(), XlsxError> {
+    worksheet.write(*row, 0, name)?;
+    worksheet.write_with_format(*row, 1, value, format)?;
+    *row += 1;
+    Ok(())
+}
+
+/// Line charts of every plate temperature and composition against the local time
+fn write_charts(worksheet: &mut Worksheet, samples: usize, plates: usize) -> Result<(), XlsxError> {
+    worksheet.set_name(CHARTS_SHEET)?;
+    if samples == 0 {
+        return Ok(());
+    }
+    let last_row = samples as u32;
+    let first_temperature = 3u16;
+    let first_composition = first_temperature + plates as u16;
+
+    let charts = [
+        ("Temperatures", "Temperature [°C]", first_temperature, 1),
+        (
+            "Compositions",
+            "Composition [mol frac]",
+            first_composition,
+            23,
+        ),
+    ];
+    for (title, axis, first_column, at) in charts {
+        let mut chart = Chart::new(ChartType::Line);
+        chart.title().set_name(title);
+        chart.x_axis().set_name("Time").set_num_format(TIME_FORMAT);
+        chart.y_axis().set_name(axis);
+        chart.set_width(960).set_height(400);
+        for plate in 0..plates as u16 {
+            let column = first_column + plate;
+            chart
+                .add_series()
+                .set_name((DATA_SHEET, 0, column))
+                .set_categories((DATA_SHEET, 1, 1, last_row, 1))
+                .set_values((DATA_SHEET, 1, column, last_row, column));
+        }
+        worksheet.insert_chart(at, 1, &chart)?;
+    }
+    Ok(())
+}
+
+fn header_format() -> Format {
+    Format::new()
+        .set_bold()
+        .set_border_bottom(FormatBorder::Thin)
+}
+
+/// Excel serial date of a timestamp in milliseconds, shifted to local time
+fn local_time(timestamp: u64, utc_offset: i32) -> f64 {
+    let local = timestamp as f64 + utc_offset as f64 * 60_000.0;
+    UNIX_EPOCH_SERIAL + local / MS_PER_DAY
+}
+
+/// Flattens the settings JSON into `path = value` pairs, e.g. `serial.dataBits`
+fn flatten_settings(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_settings(&path, value, rows);
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten_settings(&format!("{}[{}]", prefix, i), value, rows);
+            }
+        }
+        Value::String(text) => rows.push((prefix.to_string(), text.clone())),
+        Value::Null => rows.push((prefix.to_string(), String::new())),
+        other => rows.push((prefix.to_string(), other.to_string())),
+    }
+}
@@ -1,26 +1,35 @@
 mod commands;
-use commands::data_manager::{DataSource, MeasurementHistory};
-use commands::emitter::{cancel_column_data, pause_column_data, send_column_data};
+use commands::calculations::equilibrium_curve;
+use commands::data_manager::DataSource;
+use commands::emitter::{
+    acquisition_status, pause_acquisition, resume_acquisition, start_acquisition, stop_acquisition,
+    Acquisition,
+};
+use commands::history::{query_history, MeasurementHistory};
 use commands::modbus_serial::{
     available_ports, connect_modbus, disconnect_modbus, is_connected, read_coils,
-    read_holding_registers, write_single_coil, write_single_register, CurrentConnection,
+    read_discrete_inputs, read_holding_registers, read_input_registers, write_multiple_coils,
+    write_multiple_registers, write_single_coil, write_single_register, CurrentConnection,
+};
+use commands::playback::{
+    playback_position, recompute_compositions, seek_playback, set_playback_loop,
+    set_playback_recompute, set_playback_speed, step_playback,
 };
+use commands::run_log::{export_run, list_runs, open_run, RunLog};
 use commands::settings::{get_settings, save_settings, SettingsState};
+use commands::supervisor::connection_status;
+use commands::thermodynamics::available_mixtures;
 use commands::utils::{export_data, file_path, folder_path, import_data};
 use tokio::sync::Mutex;
 
-#[derive(Debug)]
-pub struct TransmissionState {
-    pub is_running: bool,
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let settings = Mutex::new(SettingsState::default());
     let connection = Mutex::new(CurrentConnection::default());
     let measurement_history = Mutex::new(MeasurementHistory::default());
     let data_source = Mutex::new(DataSource::Live);
-    let transmission_state = Mutex::new(TransmissionState { is_running: false });
+    let acquisition = Mutex::new(Acquisition::default());
+    let run_log = Mutex::new(RunLog::default());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -35,25 +44,46 @@ pub fn run() {
         .manage(connection)
         .manage(measurement_history)
         .manage(data_source)
-        .manage(transmission_state)
+        .manage(acquisition)
+        .manage(run_log)
         .invoke_handler(tauri::generate_handler![
             connect_modbus,
             disconnect_modbus,
             read_coils,
+            read_discrete_inputs,
             read_holding_registers,
+            read_input_registers,
             write_single_coil,
             write_single_register,
+            write_multiple_coils,
+            write_multiple_registers,
             is_connected,
+            connection_status,
             available_ports,
+            available_mixtures,
+            equilibrium_curve,
             get_settings,
             save_settings,
             export_data,
             import_data,
+            query_history,
+            list_runs,
+            open_run,
+            export_run,
             folder_path,
             file_path,
-            send_column_data,
-            cancel_column_data,
-            pause_column_data
+            start_acquisition,
+            pause_acquisition,
+            resume_acquisition,
+            stop_acquisition,
+            acquisition_status,
+            playback_position,
+            set_playback_speed,
+            set_playback_loop,
+            seek_playback,
+            step_playback,
+            set_playback_recompute,
+            recompute_compositions
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
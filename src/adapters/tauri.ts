@@ -1,25 +1,46 @@
 import { invoke } from "@tauri-apps/api/core";
 import { error, info, warn, trace, debug } from "@tauri-apps/plugin-log";
+import { AppError } from "@/types";
 
 type CommandType =
   | "connect_modbus"
   | "disconnect_modbus"
   | "read_coils"
+  | "read_discrete_inputs"
   | "read_holding_registers"
+  | "read_input_registers"
   | "write_single_coil"
   | "write_single_register"
+  | "write_multiple_coils"
+  | "write_multiple_registers"
   | "is_connected"
+  | "connection_status"
   | "available_ports"
+  | "available_mixtures"
+  | "equilibrium_curve"
   | "save_settings"
   | "get_settings"
   | "export_data"
   | "import_data"
+  | "query_history"
+  | "list_runs"
+  | "open_run"
+  | "export_run"
   | "folder_path"
   | "file_path"
   | "active_column_data"
-  | "cancel_column_data"
-  | "send_column_data"
-  | "pause_column_data";
+  | "start_acquisition"
+  | "pause_acquisition"
+  | "resume_acquisition"
+  | "stop_acquisition"
+  | "acquisition_status"
+  | "playback_position"
+  | "set_playback_speed"
+  | "set_playback_loop"
+  | "seek_playback"
+  | "step_playback"
+  | "set_playback_recompute"
+  | "recompute_compositions";
 
 export const invokeTauri = async <T>(
   command: CommandType,
@@ -28,6 +49,20 @@ export const invokeTauri = async <T>(
   return await invoke<T>(command, payload);
 };
 
+export const isAppError = (value: unknown): value is AppError =>
+  typeof value === "object" && value !== null && "code" in value;
+
+export const errorMessage = (value: unknown, fallback = "Unexpected error") => {
+  if (typeof value === "string") return value;
+  if (isAppError(value) && value.errors) {
+    return value.errors
+      .map((error) => `${error.field} ${error.message}`)
+      .join("; ");
+  }
+  if (isAppError(value)) return value.message ?? fallback;
+  return fallback;
+};
+
 export const logger = {
   error,
   info,
@@ -0,0 +1,70 @@
+import { Label } from "@/components/ui/label";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { CSV_DELIMITERS, DECIMAL_SEPARATORS } from "@/constants";
+import { CsvOptionsType } from "@/types";
+
+export function CsvOptions({
+  value,
+  onChange,
+}: {
+  value: CsvOptionsType;
+  onChange: (value: CsvOptionsType) => void;
+}) {
+  return (
+    <>
+      <div className="grid grid-cols-4 items-center gap-4">
+        <Label htmlFor="delimiter" className="text-right">
+          Delimiter
+        </Label>
+        <Select
+          value={value.delimiter}
+          onValueChange={(delimiter) => onChange({ ...value, delimiter })}
+        >
+          <SelectTrigger id="delimiter" className="col-span-3">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {CSV_DELIMITERS.map((delimiter) => (
+              <SelectItem key={delimiter.id} value={delimiter.id}>
+                {delimiter.name}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      </div>
+      <div className="grid grid-cols-4 items-center gap-4">
+        <Label htmlFor="decimal-separator" className="text-right">
+          Decimal
+        </Label>
+        <Select
+          value={value.decimalSeparator}
+          onValueChange={(decimalSeparator) =>
+            onChange({ ...value, decimalSeparator })
+          }
+        >
+          <SelectTrigger id="decimal-separator" className="col-span-3">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {DECIMAL_SEPARATORS.map((separator) => (
+              <SelectItem key={separator.id} value={separator.id}>
+                {separator.name}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      </div>
+      {value.delimiter === value.decimalSeparator && (
+        <p className="text-center text-xs text-destructive">
+          Delimiter and decimal separator must differ
+        </p>
+      )}
+    </>
+  );
+}
@@ -1,4 +1,4 @@
-import { invokeTauri } from "@/adapters/tauri";
+import { errorMessage, invokeTauri } from "@/adapters/tauri";
 import { Button } from "@/components/ui/button";
 import {
   Dialog,
@@ -9,6 +9,16 @@ import {
 } from "@/components/ui/dialog";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { CsvOptions } from "@/components/csv-options";
+import { DEFAULT_CSV_OPTIONS } from "@/constants";
+import { CsvOptionsType } from "@/types";
 import { useState } from "react";
 import { toast } from "sonner";
 
@@ -16,14 +26,21 @@ export function ExportDialog({ children }: { children: React.ReactNode }) {
   const [isOpen, setIsOpen] = useState(false);
   const [fileName, setFileName] = useState("column-data");
   const [folderPath, setFolderPath] = useState("");
+  const [format, setFormat] = useState<"xlsx" | "csv">("xlsx");
+  const [operator, setOperator] = useState("");
+  const [csvOptions, setCsvOptions] =
+    useState<CsvOptionsType>(DEFAULT_CSV_OPTIONS);
   const handleExport = () => {
     toast.promise(
       invokeTauri("export_data", {
-        path: folderPath + "/" + fileName + ".xlsx",
+        path: folderPath + "/" + fileName + "." + format,
+        csv: csvOptions,
+        operator,
+        utcOffset: -new Date().getTimezoneOffset(),
       }),
       {
         loading: "Saving data...",
-        error: "Error saving data",
+        error: (error) => errorMessage(error, "Error saving data"),
         success: "Data saved",
       },
     );
@@ -68,6 +85,38 @@ export function ExportDialog({ children }: { children: React.ReactNode }) {
               {folderPath ? folderPath : "Choose path"}
             </Button>
           </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="format" className="text-right">
+              Format
+            </Label>
+            <Select
+              value={format}
+              onValueChange={(value) => setFormat(value as "xlsx" | "csv")}
+            >
+              <SelectTrigger id="format" className="col-span-3">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="xlsx">Excel (.xlsx)</SelectItem>
+                <SelectItem value="csv">CSV (.csv)</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+          {format === "csv" ? (
+            <CsvOptions value={csvOptions} onChange={setCsvOptions} />
+          ) : (
+            <div className="grid grid-cols-4 items-center gap-4">
+              <Label htmlFor="operator" className="text-right">
+                Operator
+              </Label>
+              <Input
+                id="operator"
+                value={operator}
+                onChange={(e) => setOperator(e.target.value)}
+                className="col-span-3"
+              />
+            </div>
+          )}
         </div>
         <Button onClick={handleExport}>Descargar</Button>
       </DialogContent>
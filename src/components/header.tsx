@@ -1,33 +1,53 @@
-import { invokeTauri, logger } from "@/adapters/tauri";
+import { errorMessage, invokeTauri, logger } from "@/adapters/tauri";
 import { Button } from "@/components/ui/button";
 import { useData } from "@/hooks/useData";
-import { Pause, Play, Power, Save, Settings } from "lucide-react";
+import { AcquisitionStatus } from "@/types";
+import { History, Pause, Play, Power, Save, Settings } from "lucide-react";
 import { toast } from "sonner";
 import { ExportDialog } from "./export-dialog";
 import { ImportDialog } from "./import-dialog";
+import { PlaybackControls, playbackCommand } from "./playback-controls";
+import { RunsDialog } from "./runs-dialog";
 import { SettingsDialog } from "./settings-dialog";
 import { Progress } from "./ui/progress";
 import { StatusLed } from "./ui/status-led";
 
 export function Header({ className }: { className?: string }) {
   const connected = useData((state) => state.connected);
+  const linkState = useData((state) => state.linkState);
   const setConnected = useData((state) => state.setConnected);
   const percentageComplete = useData((state) => state.fileProgress);
   const clearData = useData((state) => state.clearData);
+  const playback = useData((state) => state.playback);
 
   const handleFile = async () => {
-    if (percentageComplete === 100) return;
+    if (percentageComplete === 100 && !playback?.looping) return;
     if (connected === "file") {
-      await invokeTauri("pause_column_data")
+      await invokeTauri("pause_acquisition")
         .then(() => setConnected("paused"))
         .catch((error) => {
-          logger.error(`Error canceling column data: ${error}`);
+          logger.error(`Error pausing acquisition: ${errorMessage(error)}`);
           setConnected("none");
         });
       return;
     }
+    if (connected !== "paused") return;
     setConnected("file");
-    await invokeTauri("send_column_data").catch(() => setConnected("none"));
+    // a finished playback is idle and starts again from where it was moved to
+    const { state } =
+      await invokeTauri<AcquisitionStatus>("acquisition_status");
+    await invokeTauri(
+      state === "idle" ? "start_acquisition" : "resume_acquisition",
+    ).catch(() => setConnected("none"));
+  };
+
+  const handleSeek = (event: React.MouseEvent<HTMLDivElement>) => {
+    if (!playback) return;
+    const { left, width } = event.currentTarget.getBoundingClientRect();
+    const fraction = (event.clientX - left) / width;
+    playbackCommand("seek_playback", {
+      index: Math.round(fraction * playback.total),
+    });
   };
 
   const handleConnection = async () => {
@@ -47,7 +67,7 @@ export function Header({ className }: { className?: string }) {
       toast.promise(
         invokeTauri("connect_modbus").then(() => {
           setConnected("modbus");
-          invokeTauri("send_column_data");
+          invokeTauri("start_acquisition");
         }),
         {
           loading: "Connecting to MODBUS...",
@@ -85,19 +105,34 @@ export function Header({ className }: { className?: string }) {
             Connect MODBUS
           </Button>
           <ExportDialog>
-            <Button variant={"outline"} className="rounded-l-none">
+            <Button variant={"outline"} className="rounded-none border-r-0">
               <Save className="h-4 w-4" />
               Save
             </Button>
           </ExportDialog>
-          <StatusLed connected={connected === "modbus"} className="ml-4" />
+          <RunsDialog>
+            <Button variant={"outline"} className="rounded-l-none">
+              <History className="h-4 w-4" />
+              Runs
+            </Button>
+          </RunsDialog>
+          <StatusLed
+            connected={connected === "modbus"}
+            warning={linkState === "degraded" || linkState === "reconnecting"}
+            className="ml-4"
+          />
         </div>
         <div className="flex flex-col gap-2">
           <div className="flex items-center">
-            <span className="text-xs text-muted-foreground">
+            <PlaybackControls />
+            <span className="ml-2 text-xs text-muted-foreground">
               {percentageComplete.toFixed(2)}%
             </span>
-            <Progress value={percentageComplete} className="mx-2 h-2 w-40" />
+            <Progress
+              value={percentageComplete}
+              className={`mx-2 h-2 w-40 ${playback ? "cursor-pointer" : ""}`}
+              onClick={handleSeek}
+            />
             <Button
               variant="outline"
               size="icon"
@@ -1,4 +1,4 @@
-import { invokeTauri, logger } from "@/adapters/tauri";
+import { errorMessage, invokeTauri, logger } from "@/adapters/tauri";
 import { Button } from "@/components/ui/button";
 import {
   Dialog,
@@ -14,10 +14,15 @@ import { cn } from "@/lib/utils";
 import { FileSpreadsheet, Upload, X } from "lucide-react";
 import { useState } from "react";
 import { toast } from "sonner";
+import { CsvOptionsType, ImportReportType } from "@/types";
+import { CsvOptions } from "@/components/csv-options";
+import { DEFAULT_CSV_OPTIONS } from "@/constants";
 
 export function ImportDialog({ children }: { children: React.ReactNode }) {
   const [isOpen, setIsOpen] = useState(false);
   const [isImporting, setIsImporting] = useState(false);
+  const [csvOptions, setCsvOptions] =
+    useState<CsvOptionsType>(DEFAULT_CSV_OPTIONS);
   const setFilePath = useData((state) => state.setFilePath);
   const filePath = useData((state) => state.filePath) as string | null;
   const connected = useData((state) => state.connected);
@@ -34,22 +39,34 @@ export function ImportDialog({ children }: { children: React.ReactNode }) {
 
     const handleFile = async () => {
       try {
-        await invokeTauri("import_data", {
+        const report = await invokeTauri<ImportReportType>("import_data", {
           path: filePath,
+          csv: csvOptions,
         });
 
-        invokeTauri("send_column_data");
+        invokeTauri("start_acquisition");
+        return report;
       } catch (error) {
-        throw new Error("Failed to import data");
+        throw new Error(errorMessage(error, "Failed to import data"));
       }
     };
 
     toast.promise(handleFile(), {
       loading: "Importing data...",
-      error: "Failed to import data",
-      success: () => {
+      error: (error) => error.message,
+      success: (report) => {
         setConnected("file");
-        return "Data imported successfully";
+        const rejected = report.rowsRejected.map(
+          ({ row, reason }) => `Row ${row}: ${reason}`,
+        );
+        if (rejected.length > 0) {
+          logger.warn(rejected.join("\n"));
+          toast.warning(`${rejected.length} rows rejected`, {
+            description: rejected.slice(0, 3).join("\n"),
+          });
+        }
+        const source = report.sheet ? ` (sheet ${report.sheet})` : "";
+        return `Imported ${report.rowsImported} rows, ${report.plateCount} plates${source}`;
       },
       finally: () => {
         setIsImporting(false);
@@ -90,10 +107,10 @@ export function ImportDialog({ children }: { children: React.ReactNode }) {
         <DialogHeader>
           <DialogTitle className="flex items-center gap-2">
             <FileSpreadsheet className="h-5 w-5 text-primary" />
-            Excel file
+            Data file
           </DialogTitle>
           <DialogDescription>
-            Select an Excel file to import data into the application.
+            Select an Excel or CSV file to import data into the application.
           </DialogDescription>
         </DialogHeader>
         {!filePath ? (
@@ -103,10 +120,10 @@ export function ImportDialog({ children }: { children: React.ReactNode }) {
           >
             <Upload className="mx-auto mb-4 h-10 w-10 text-muted-foreground/50" />
             <p className="text-sm font-medium text-muted-foreground">
-              Click to select an Excel file
+              Click to select a data file
             </p>
             <p className="mt-2 text-xs text-muted-foreground">
-              Supported formats: .xlsx, .xls
+              Supported formats: .xlsx, .csv
             </p>
           </div>
         ) : (
@@ -134,6 +151,11 @@ export function ImportDialog({ children }: { children: React.ReactNode }) {
             </div>
           </div>
         )}
+        {filePath?.toLowerCase().endsWith(".csv") && (
+          <div className="grid gap-4">
+            <CsvOptions value={csvOptions} onChange={setCsvOptions} />
+          </div>
+        )}
         <DialogFooter className="gap-2">
           <Button
             variant="outline"
@@ -0,0 +1,129 @@
+import { errorMessage, invokeTauri } from "@/adapters/tauri";
+import { Button } from "@/components/ui/button";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { PLAYBACK_SPEEDS } from "@/constants";
+import { useData } from "@/hooks/useData";
+import { PlaybackPositionType } from "@/types";
+import {
+  Calculator,
+  Clock,
+  Repeat,
+  StepBack,
+  StepForward,
+} from "lucide-react";
+import { useEffect } from "react";
+import { toast } from "sonner";
+import { RecomputeDialog } from "./recompute-dialog";
+
+type PlaybackCommand =
+  | "set_playback_speed"
+  | "set_playback_loop"
+  | "seek_playback"
+  | "step_playback"
+  | "set_playback_recompute";
+
+export const playbackCommand = (
+  command: PlaybackCommand,
+  payload: Record<string, unknown>,
+) =>
+  invokeTauri<PlaybackPositionType>(command, payload)
+    .then(useData.getState().setPlayback)
+    .catch((error) => toast.error(errorMessage(error, "Playback error")));
+
+export function PlaybackControls() {
+  const playback = useData((state) => state.playback);
+  const connected = useData((state) => state.connected);
+  const setPlayback = useData((state) => state.setPlayback);
+
+  useEffect(() => {
+    if (connected !== "file" && connected !== "paused") return;
+    invokeTauri<PlaybackPositionType>("playback_position")
+      .then(setPlayback)
+      .catch(() => {});
+  }, [connected]);
+
+  if (!playback || (connected !== "file" && connected !== "paused")) {
+    return null;
+  }
+
+  const recorded = playback.pacing === "recorded";
+
+  return (
+    <div className="flex items-center gap-1">
+      <span className="mr-1 text-xs text-muted-foreground">
+        {playback.index}/{playback.total}
+      </span>
+      <Button
+        variant="outline"
+        size="icon"
+        title="Step back"
+        onClick={() => playbackCommand("step_playback", { steps: -1 })}
+      >
+        <StepBack className="h-4 w-4" />
+      </Button>
+      <Button
+        variant="outline"
+        size="icon"
+        title="Step forward"
+        onClick={() => playbackCommand("step_playback", { steps: 1 })}
+      >
+        <StepForward className="h-4 w-4" />
+      </Button>
+      <Select
+        value={playback.speed.toString()}
+        onValueChange={(speed) =>
+          playbackCommand("set_playback_speed", { speed: Number(speed) })
+        }
+      >
+        <SelectTrigger className="h-9 w-20">
+          <SelectValue />
+        </SelectTrigger>
+        <SelectContent>
+          {PLAYBACK_SPEEDS.map((speed) => (
+            <SelectItem key={speed} value={speed.toString()}>
+              {speed}x
+            </SelectItem>
+          ))}
+        </SelectContent>
+      </Select>
+      <Button
+        variant={recorded ? "default" : "outline"}
+        size="icon"
+        title="Recorded timing"
+        onClick={() =>
+          playbackCommand("set_playback_speed", {
+            speed: playback.speed,
+            pacing: recorded ? "samplePeriod" : "recorded",
+          })
+        }
+      >
+        <Clock className="h-4 w-4" />
+      </Button>
+      <Button
+        variant={playback.looping ? "default" : "outline"}
+        size="icon"
+        title="Loop"
+        onClick={() =>
+          playbackCommand("set_playback_loop", { looping: !playback.looping })
+        }
+      >
+        <Repeat className="h-4 w-4" />
+      </Button>
+      <RecomputeDialog>
+        <Button
+          variant={playback.recompute ? "default" : "outline"}
+          size="icon"
+          title="Recompute compositions"
+        >
+          <Calculator className="h-4 w-4" />
+        </Button>
+      </RecomputeDialog>
+    </div>
+  );
+}
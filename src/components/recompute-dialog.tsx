@@ -0,0 +1,127 @@
+import { errorMessage, invokeTauri } from "@/adapters/tauri";
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableHeader,
+  TableRow,
+} from "@/components/ui/table";
+import { useData } from "@/hooks/useData";
+import { RecomputeReportType } from "@/types";
+import { useState } from "react";
+import { toast } from "sonner";
+import { playbackCommand } from "./playback-controls";
+
+export function RecomputeDialog({ children }: { children: React.ReactNode }) {
+  const [isOpen, setIsOpen] = useState(false);
+  const [pressure, setPressure] = useState("");
+  const [report, setReport] = useState<RecomputeReportType | null>(null);
+  const recompute = useData((state) => state.playback?.recompute);
+
+  const pressureValue = pressure.trim() === "" ? null : Number(pressure);
+
+  const handleCompare = (apply: boolean) => {
+    toast.promise(
+      invokeTauri<RecomputeReportType>("recompute_compositions", {
+        pressure: pressureValue,
+        apply,
+      }),
+      {
+        loading: "Recomputing compositions...",
+        error: (error) => errorMessage(error, "Error recomputing compositions"),
+        success: (report) => {
+          setReport(report);
+          return report.applied
+            ? `Recomputed compositions applied to ${report.entries} entries`
+            : `Recomputed ${report.entries} entries`;
+        },
+      },
+    );
+  };
+
+  const handleToggle = () =>
+    playbackCommand("set_playback_recompute", {
+      enabled: !recompute,
+      pressure: pressureValue,
+    });
+
+  return (
+    <Dialog open={isOpen} onOpenChange={setIsOpen}>
+      <DialogTrigger asChild>{children}</DialogTrigger>
+      <DialogContent className="sm:max-w-[500px]">
+        <DialogHeader>
+          <DialogTitle>Recompute compositions</DialogTitle>
+          <DialogDescription>
+            Computes the compositions again from the recorded temperatures with
+            the mixture and activity model in the current settings.
+          </DialogDescription>
+        </DialogHeader>
+        <div className="grid gap-4 py-4">
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="recompute-pressure" className="text-right">
+              Pressure
+            </Label>
+            <Input
+              id="recompute-pressure"
+              type="number"
+              placeholder="Recorded (mmHg)"
+              value={pressure}
+              onChange={(e) => setPressure(e.target.value)}
+              className="col-span-3"
+            />
+          </div>
+          {report && (
+            <>
+              <p className="text-sm text-muted-foreground">
+                {report.entries} entries, {report.outOfRange} out of range,{" "}
+                {report.solverFailures} solver failures
+              </p>
+              <div className="max-h-[250px] overflow-y-auto">
+                <Table>
+                  <TableHeader>
+                    <TableRow>
+                      <TableHead>Plate</TableHead>
+                      <TableHead>Mean deviation</TableHead>
+                      <TableHead>Max deviation</TableHead>
+                    </TableRow>
+                  </TableHeader>
+                  <TableBody>
+                    {report.plates.map((deviation) => (
+                      <TableRow key={deviation.plate}>
+                        <TableCell>{deviation.plate + 1}</TableCell>
+                        <TableCell>{deviation.mean.toFixed(4)}</TableCell>
+                        <TableCell>{deviation.max.toFixed(4)}</TableCell>
+                      </TableRow>
+                    ))}
+                  </TableBody>
+                </Table>
+              </div>
+            </>
+          )}
+        </div>
+        <DialogFooter>
+          <Button variant="outline" onClick={handleToggle}>
+            {recompute ? "Stop recomputing" : "Recompute while playing"}
+          </Button>
+          <Button variant="outline" onClick={() => handleCompare(false)}>
+            Compare
+          </Button>
+          <Button onClick={() => handleCompare(true)}>Apply</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,164 @@
+import { errorMessage, invokeTauri } from "@/adapters/tauri";
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import {
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableHeader,
+  TableRow,
+} from "@/components/ui/table";
+import { useData } from "@/hooks/useData";
+import { ImportReportType, RunSummaryType } from "@/types";
+import { Download, FolderOpen } from "lucide-react";
+import { useState } from "react";
+import { toast } from "sonner";
+
+const formatDate = (milliseconds: number) =>
+  new Date(milliseconds).toLocaleString();
+
+export function RunsDialog({ children }: { children: React.ReactNode }) {
+  const [isOpen, setIsOpen] = useState(false);
+  const [runs, setRuns] = useState<RunSummaryType[]>([]);
+  const connected = useData((state) => state.connected);
+  const setConnected = useData((state) => state.setConnected);
+
+  const loadRuns = async () => {
+    try {
+      setRuns(await invokeTauri<RunSummaryType[]>("list_runs"));
+    } catch (error) {
+      toast.error(errorMessage(error, "Error listing runs"));
+    }
+  };
+
+  const handleOpenChange = (open: boolean) => {
+    setIsOpen(open);
+    if (open) loadRuns();
+  };
+
+  const handleOpen = (run: RunSummaryType) => {
+    const openRun = async () => {
+      try {
+        const report = await invokeTauri<ImportReportType>("open_run", {
+          id: run.id,
+        });
+        invokeTauri("start_acquisition");
+        return report;
+      } catch (error) {
+        throw new Error(errorMessage(error, "Error opening run"));
+      }
+    };
+
+    toast.promise(openRun(), {
+      loading: "Opening run...",
+      error: (error) => error.message,
+      success: (report) => {
+        setConnected("file");
+        setIsOpen(false);
+        const rejected = report.rowsRejected.length;
+        return rejected > 0
+          ? `Opened ${report.rowsImported} entries, ${rejected} damaged lines skipped`
+          : `Opened ${report.rowsImported} entries`;
+      },
+    });
+  };
+
+  const handleExport = async (run: RunSummaryType) => {
+    const folderPath = await invokeTauri<string>("folder_path");
+    if (!folderPath) return;
+    toast.promise(
+      invokeTauri("export_run", {
+        id: run.id,
+        path: folderPath + "/" + run.id + ".xlsx",
+        utcOffset: -new Date().getTimezoneOffset(),
+      }),
+      {
+        loading: "Saving run...",
+        error: (error) => errorMessage(error, "Error saving run"),
+        success: "Run saved",
+      },
+    );
+  };
+
+  return (
+    <Dialog open={isOpen} onOpenChange={handleOpenChange}>
+      <DialogTrigger asChild>{children}</DialogTrigger>
+      <DialogContent className="sm:max-w-[700px]">
+        <DialogHeader>
+          <DialogTitle>Runs</DialogTitle>
+          <DialogDescription>
+            Every acquisition is logged to disk as it runs. Open a run to
+            replay it or save it as an Excel file.
+          </DialogDescription>
+        </DialogHeader>
+        <div className="max-h-[400px] overflow-y-auto">
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>Start</TableHead>
+                <TableHead>End</TableHead>
+                <TableHead>Samples</TableHead>
+                <TableHead>Mixture</TableHead>
+                <TableHead />
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {runs.length === 0 && (
+                <TableRow>
+                  <TableCell
+                    colSpan={5}
+                    className="text-center text-muted-foreground"
+                  >
+                    No runs logged yet
+                  </TableCell>
+                </TableRow>
+              )}
+              {runs.map((run) => (
+                <TableRow key={run.id}>
+                  <TableCell>{formatDate(run.startedAt)}</TableCell>
+                  <TableCell>
+                    {run.active
+                      ? "Running"
+                      : run.endedAt
+                        ? formatDate(run.endedAt)
+                        : "-"}
+                  </TableCell>
+                  <TableCell>{run.samples}</TableCell>
+                  <TableCell>{run.mixture ?? "-"}</TableCell>
+                  <TableCell className="flex justify-end gap-1">
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      title="Open"
+                      disabled={connected !== "none" || run.samples === 0}
+                      onClick={() => handleOpen(run)}
+                    >
+                      <FolderOpen className="h-4 w-4" />
+                    </Button>
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      title="Save as Excel"
+                      disabled={run.samples === 0}
+                      onClick={() => handleExport(run)}
+                    >
+                      <Download className="h-4 w-4" />
+                    </Button>
+                  </TableCell>
+                </TableRow>
+              ))}
+            </TableBody>
+          </Table>
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -1,4 +1,9 @@
-import { invokeTauri, logger } from "@/adapters/tauri";
+import {
+  errorMessage,
+  invokeTauri,
+  isAppError,
+  logger,
+} from "@/adapters/tauri";
 import { Button } from "@/components/ui/button";
 import {
   Dialog,
@@ -25,9 +30,15 @@ import {
   SelectTrigger,
   SelectValue,
 } from "@/components/ui/select";
+import {
+  ACTIVITY_MODELS,
+  FLOW_CONTROLS,
+  PARITIES,
+  TRANSPORTS,
+} from "@/constants";
 import { useSettings } from "@/hooks/useSettings";
-import { formSchema } from "@/schemas/settings";
-import { SettingsType } from "@/types";
+import { FORM_FIELDS, formSchema } from "@/schemas/settings";
+import { MixtureInfo, SettingsType } from "@/types";
 import { zodResolver } from "@hookform/resolvers/zod";
 import { useEffect, useState } from "react";
 import { useForm } from "react-hook-form";
@@ -39,55 +50,131 @@ export function SettingsDialog({ children }: { children: React.ReactNode }) {
   const form = useForm<z.infer<typeof formSchema>>({
     resolver: zodResolver(formSchema),
     defaultValues: {
+      transport: settings.transport,
+      host: settings.network.host,
+      port: settings.network.port,
       baudrate: settings.baudrate.toString() as unknown as number,
+      dataBits: settings.serial.dataBits.toString() as unknown as number,
+      parity: settings.serial.parity,
+      stopBits: settings.serial.stopBits.toString() as unknown as number,
+      flowControl: settings.serial.flowControl,
       unitId: settings.unitId,
       timeout: settings.timeout,
+      samplePeriodMs: settings.acquisition.samplePeriodMs,
       temperatureBottom: settings.temperatureAddress.bottom,
       temperatureTop: settings.temperatureAddress.top,
       usbPort: settings.usbPort,
+      mixture: settings.mixture,
+      pressure: settings.pressure.value,
+      activityModel: settings.activityModel,
     },
   });
 
   const [open, setOpen] = useState(false);
   const [usbPorts, setUsbPorts] = useState<string[]>([]);
+  const [mixtures, setMixtures] = useState<MixtureInfo[]>([]);
+  const transport = form.watch("transport");
 
   async function onSubmit(values: z.infer<typeof formSchema>) {
     console.log("newSettings");
     const {
+      transport,
       usbPort,
+      host,
+      port,
       baudrate,
+      dataBits,
+      parity,
+      stopBits,
+      flowControl,
       temperatureBottom,
       temperatureTop,
       timeout,
+      samplePeriodMs,
       unitId,
+      mixture,
+      pressure,
+      activityModel,
     } = values;
     try {
       const newSettings: Partial<SettingsType> = {
+        ...settings,
+        transport,
         usbPort,
+        network: { host, port },
         baudrate,
+        serial: {
+          dataBits: dataBits as 7 | 8,
+          parity,
+          stopBits: stopBits as 1 | 2,
+          flowControl,
+        },
         temperatureAddress: {
           bottom: temperatureBottom,
           top: temperatureTop,
         },
         timeout,
+        acquisition: { ...settings.acquisition, samplePeriodMs },
         unitId,
-        count: 2,
+        mixture,
+        pressure: { ...settings.pressure, value: pressure },
+        activityModel,
       };
       await saveSettings(newSettings);
       setOpen(false);
     } catch (error) {
-      toast.error("Error on save");
-      logger.error("Error on save: " + error);
+      // rejected fields of the form are shown next to them
+      const fieldErrors = (isAppError(error) && error.errors) || [];
+      fieldErrors.forEach(({ field, message }) => {
+        const formField = FORM_FIELDS[field];
+        if (formField) form.setError(formField, { message });
+      });
+      const others = fieldErrors.filter(({ field }) => !FORM_FIELDS[field]);
+      if (others.length > 0) {
+        toast.error(errorMessage({ code: "settingsRejected", errors: others }));
+      } else {
+        toast.error(
+          fieldErrors.length > 0
+            ? "Invalid settings"
+            : errorMessage(error, "Error on save"),
+        );
+      }
+      logger.error("Error on save: " + errorMessage(error));
     }
   }
 
   useEffect(() => {
-    const { baudrate, temperatureAddress, timeout, unitId, usbPort } = settings;
+    const {
+      transport,
+      network,
+      baudrate,
+      serial,
+      temperatureAddress,
+      timeout,
+      acquisition,
+      unitId,
+      usbPort,
+      mixture,
+      pressure,
+      activityModel,
+    } = settings;
+    form.setValue("transport", transport);
+    form.setValue("host", network.host);
+    form.setValue("port", network.port);
     form.setValue("baudrate", baudrate.toString() as unknown as number);
+    form.setValue("dataBits", serial.dataBits.toString() as unknown as number);
+    form.setValue("parity", serial.parity);
+    form.setValue("stopBits", serial.stopBits.toString() as unknown as number);
+    form.setValue("flowControl", serial.flowControl);
     form.setValue("temperatureBottom", temperatureAddress.bottom);
     form.setValue("temperatureTop", temperatureAddress.top);
     form.setValue("timeout", timeout);
+    form.setValue("samplePeriodMs", acquisition.samplePeriodMs);
     form.setValue("unitId", unitId);
+    form.setValue("mixture", mixture);
+    form.setValue("pressure", pressure.value);
+    form.setValue("activityModel", activityModel);
+    invokeTauri<MixtureInfo[]>("available_mixtures").then(setMixtures);
     invokeTauri<string[]>("available_ports").then((ports) => {
       if (ports.length === 0) {
         setUsbPorts([]);
@@ -118,25 +205,20 @@ export function SettingsDialog({ children }: { children: React.ReactNode }) {
           <form onSubmit={form.handleSubmit(onSubmit)}>
             <FormField
               control={form.control}
-              name="usbPort"
+              name="transport"
               render={({ field }) => (
                 <FormItem className="flex items-center justify-between gap-4">
-                  <FormLabel>Puerto USB</FormLabel>
+                  <FormLabel>Transporte</FormLabel>
                   <Select onValueChange={field.onChange} value={field.value}>
                     <FormControl>
                       <SelectTrigger className="w-[200px]">
-                        <SelectValue placeholder="Selecciona un puerto" />
+                        <SelectValue placeholder="Selecciona un transporte" />
                       </SelectTrigger>
                     </FormControl>
                     <SelectContent>
-                      {usbPorts.length === 0 && (
-                        <SelectItem value={" "} disabled>
-                          No hay puertos...
-                        </SelectItem>
-                      )}
-                      {usbPorts.map((port) => (
-                        <SelectItem key={port} value={port}>
-                          {port}
+                      {TRANSPORTS.map((transport) => (
+                        <SelectItem key={transport.id} value={transport.id}>
+                          {transport.name}
                         </SelectItem>
                       ))}
                     </SelectContent>
@@ -145,12 +227,189 @@ export function SettingsDialog({ children }: { children: React.ReactNode }) {
                 </FormItem>
               )}
             />
+            {transport === "rtuSerial" ? (
+              <>
+                <FormField
+                  control={form.control}
+                  name="usbPort"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Puerto USB</FormLabel>
+                      <Select onValueChange={field.onChange} value={field.value}>
+                        <FormControl>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="Selecciona un puerto" />
+                          </SelectTrigger>
+                        </FormControl>
+                        <SelectContent>
+                          {usbPorts.length === 0 && (
+                            <SelectItem value={" "} disabled>
+                              No hay puertos...
+                            </SelectItem>
+                          )}
+                          {usbPorts.map((port) => (
+                            <SelectItem key={port} value={port}>
+                              {port}
+                            </SelectItem>
+                          ))}
+                        </SelectContent>
+                      </Select>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="baudrate"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Baudrate</FormLabel>
+                      <FormControl>
+                        <Input className="w-[200px]" {...field} />
+                      </FormControl>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="dataBits"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Bits de datos</FormLabel>
+                      <Select
+                        onValueChange={field.onChange}
+                        value={field.value?.toString()}
+                      >
+                        <FormControl>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="Bits de datos" />
+                          </SelectTrigger>
+                        </FormControl>
+                        <SelectContent>
+                          <SelectItem value="7">7</SelectItem>
+                          <SelectItem value="8">8</SelectItem>
+                        </SelectContent>
+                      </Select>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="parity"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Paridad</FormLabel>
+                      <Select
+                        onValueChange={field.onChange}
+                        value={field.value?.toString()}
+                      >
+                        <FormControl>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="Selecciona la paridad" />
+                          </SelectTrigger>
+                        </FormControl>
+                        <SelectContent>
+                          {PARITIES.map((parity) => (
+                            <SelectItem key={parity.id} value={parity.id}>
+                              {parity.name}
+                            </SelectItem>
+                          ))}
+                        </SelectContent>
+                      </Select>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="stopBits"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Bits de parada</FormLabel>
+                      <Select
+                        onValueChange={field.onChange}
+                        value={field.value?.toString()}
+                      >
+                        <FormControl>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="Bits de parada" />
+                          </SelectTrigger>
+                        </FormControl>
+                        <SelectContent>
+                          <SelectItem value="1">1</SelectItem>
+                          <SelectItem value="2">2</SelectItem>
+                        </SelectContent>
+                      </Select>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="flowControl"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Control de flujo</FormLabel>
+                      <Select
+                        onValueChange={field.onChange}
+                        value={field.value?.toString()}
+                      >
+                        <FormControl>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="Selecciona el control" />
+                          </SelectTrigger>
+                        </FormControl>
+                        <SelectContent>
+                          {FLOW_CONTROLS.map((flowControl) => (
+                            <SelectItem key={flowControl.id} value={flowControl.id}>
+                              {flowControl.name}
+                            </SelectItem>
+                          ))}
+                        </SelectContent>
+                      </Select>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+              </>
+            ) : transport !== "simulated" ? (
+              <>
+                <FormField
+                  control={form.control}
+                  name="host"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Host</FormLabel>
+                      <FormControl>
+                        <Input className="w-[200px]" {...field} />
+                      </FormControl>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+                <FormField
+                  control={form.control}
+                  name="port"
+                  render={({ field }) => (
+                    <FormItem className="flex items-center justify-between gap-4">
+                      <FormLabel>Puerto TCP</FormLabel>
+                      <FormControl>
+                        <Input className="w-[200px]" {...field} />
+                      </FormControl>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
+              </>
+            ) : null}
             <FormField
               control={form.control}
-              name="baudrate"
+              name="timeout"
               render={({ field }) => (
                 <FormItem className="flex items-center justify-between gap-4">
-                  <FormLabel>Baudrate</FormLabel>
+                  <FormLabel>Timeout (ms)</FormLabel>
                   <FormControl>
                     <Input className="w-[200px]" {...field} />
                   </FormControl>
@@ -160,10 +419,10 @@ export function SettingsDialog({ children }: { children: React.ReactNode }) {
             />
             <FormField
               control={form.control}
-              name="timeout"
+              name="samplePeriodMs"
               render={({ field }) => (
                 <FormItem className="flex items-center justify-between gap-4">
-                  <FormLabel>Timeout</FormLabel>
+                  <FormLabel>Periodo de muestreo (ms)</FormLabel>
                   <FormControl>
                     <Input className="w-[200px]" {...field} />
                   </FormControl>
@@ -210,6 +469,67 @@ export function SettingsDialog({ children }: { children: React.ReactNode }) {
                 </FormItem>
               )}
             />
+            <FormField
+              control={form.control}
+              name="mixture"
+              render={({ field }) => (
+                <FormItem className="flex items-center justify-between gap-4">
+                  <FormLabel>Mezcla</FormLabel>
+                  <Select onValueChange={field.onChange} value={field.value}>
+                    <FormControl>
+                      <SelectTrigger className="w-[200px]">
+                        <SelectValue placeholder="Selecciona una mezcla" />
+                      </SelectTrigger>
+                    </FormControl>
+                    <SelectContent>
+                      {mixtures.map((mixture) => (
+                        <SelectItem key={mixture.id} value={mixture.id}>
+                          {mixture.name}
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
+            <FormField
+              control={form.control}
+              name="activityModel"
+              render={({ field }) => (
+                <FormItem className="flex items-center justify-between gap-4">
+                  <FormLabel>Modelo de actividad</FormLabel>
+                  <Select onValueChange={field.onChange} value={field.value}>
+                    <FormControl>
+                      <SelectTrigger className="w-[200px]">
+                        <SelectValue placeholder="Selecciona un modelo" />
+                      </SelectTrigger>
+                    </FormControl>
+                    <SelectContent>
+                      {ACTIVITY_MODELS.map((model) => (
+                        <SelectItem key={model.id} value={model.id}>
+                          {model.name}
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
+            <FormField
+              control={form.control}
+              name="pressure"
+              render={({ field }) => (
+                <FormItem className="flex items-center justify-between gap-4">
+                  <FormLabel>Presión (mmHg)</FormLabel>
+                  <FormControl>
+                    <Input className="w-[200px]" {...field} />
+                  </FormControl>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
             <DialogFooter className="mt-5">
               <Button variant="outline" type="button" onClick={handleCancel}>
                 Cancelar
@@ -2,16 +2,22 @@ import { cn } from "@/lib/utils";
 
 export function StatusLed({
   connected,
+  warning = false,
   className,
 }: {
   connected: boolean;
+  warning?: boolean;
   className?: string;
 }) {
   return (
     <div
       className={cn(
         "size-3 rounded-full border border-white shadow-lg",
-        connected ? "bg-green-300" : "bg-slate-300",
+        connected
+          ? warning
+            ? "bg-amber-300"
+            : "bg-green-300"
+          : "bg-slate-300",
         className,
       )}
     ></div>
@@ -1,6 +1,14 @@
-import { SettingsType } from "./types";
+import {
+  ActivityModelKind,
+  CsvOptionsType,
+  FlowControl,
+  Parity,
+  SettingsType,
+  Transport,
+} from "./types";
 
 export const DEFAULT_SETTINGS: SettingsType = {
+  version: 2,
   temperatureAddress: {
     top: 101,
     bottom: 100,
@@ -8,9 +16,108 @@ export const DEFAULT_SETTINGS: SettingsType = {
   usbPort: "",
   unitId: 10,
   baudrate: 9600,
-  count: 1,
   timeout: 1000,
-  numberPlates: 1,
+  numberPlates: 2,
+  mixture: "ethanolWater",
+  pressure: {
+    value: 585,
+    address: null,
+    dataType: "u16",
+    byteOrder: "abcd",
+    scale: 0.1,
+    offset: 0,
+    unit: "mmHg",
+  },
+  activityModel: "vanLaar",
+  activityParameters: null,
+  plateSensors: [],
+  serial: {
+    dataBits: 8,
+    parity: "none",
+    stopBits: 1,
+    flowControl: "none",
+  },
+  transport: "rtuSerial",
+  network: {
+    host: "127.0.0.1",
+    port: 502,
+  },
+  probe: {
+    function: "holdingRegisters",
+    address: null,
+    count: 1,
+    unitId: null,
+    retries: 2,
+    timeoutMs: 1000,
+    backoffMs: 1000,
+  },
+  history: {
+    rawRetentionMinutes: 360,
+    bucketSeconds: 60,
+    bucketRetentionHours: 168,
+  },
+  acquisition: {
+    samplePeriodMs: 1000,
+    missedTicks: "skip",
+  },
+  simulation: {
+    ambientTemperature: 25,
+    heatUpMinutes: 20,
+    feedComposition: 0.3,
+    refluxMin: 1,
+    refluxMax: 5,
+    refluxChangeMinutes: 30,
+    noise: 0.1,
+    faultRate: 0.001,
+    timeScale: 1,
+    seed: null,
+  },
 };
 
+export const PARITIES: { id: Parity; name: string }[] = [
+  { id: "none", name: "Ninguna" },
+  { id: "odd", name: "Impar" },
+  { id: "even", name: "Par" },
+];
+
+export const FLOW_CONTROLS: { id: FlowControl; name: string }[] = [
+  { id: "none", name: "Ninguno" },
+  { id: "software", name: "Software (XON/XOFF)" },
+  { id: "hardware", name: "Hardware (RTS/CTS)" },
+];
+
+export const TRANSPORTS: { id: Transport; name: string }[] = [
+  { id: "rtuSerial", name: "RTU serial" },
+  { id: "tcp", name: "Modbus TCP" },
+  { id: "rtuOverTcp", name: "RTU sobre TCP" },
+  { id: "simulated", name: "Simulador" },
+];
+
+export const ACTIVITY_MODELS: { id: ActivityModelKind; name: string }[] = [
+  { id: "vanLaar", name: "Van Laar" },
+  { id: "margules2", name: "Margules (2 sufijos)" },
+  { id: "margules3", name: "Margules (3 sufijos)" },
+  { id: "wilson", name: "Wilson" },
+  { id: "nrtl", name: "NRTL" },
+  { id: "uniquac", name: "UNIQUAC" },
+];
+
 export const MAX_DATA_LENGTH = 120;
+
+export const PLAYBACK_SPEEDS = [0.5, 1, 2, 5, 10, 50];
+
+export const DEFAULT_CSV_OPTIONS: CsvOptionsType = {
+  delimiter: ",",
+  decimalSeparator: ".",
+};
+
+export const CSV_DELIMITERS: { id: string; name: string }[] = [
+  { id: ",", name: "Comma (,)" },
+  { id: ";", name: "Semicolon (;)" },
+  { id: "\t", name: "Tab" },
+];
+
+export const DECIMAL_SEPARATORS: { id: string; name: string }[] = [
+  { id: ".", name: "Point (.)" },
+  { id: ",", name: "Comma (,)" },
+];
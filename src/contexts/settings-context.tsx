@@ -7,16 +7,70 @@ export const SettingsContext = createContext<SettingsContextType>(
 
 export function SettingsProvider({ children }: { children: React.ReactNode }) {
   const [settings, setSettings] = useState<SettingsType>({
+    version: 2,
     temperatureAddress: {
       top: 0,
       bottom: 0,
     },
     usbPort: "",
-    count: 2,
     timeout: 0,
     baudrate: 0,
     unitId: 0,
-    numberPlates: 1,
+    numberPlates: 2,
+    mixture: "ethanolWater",
+    pressure: {
+      value: 585,
+      address: null,
+      dataType: "u16",
+      byteOrder: "abcd",
+      scale: 0.1,
+      offset: 0,
+      unit: "mmHg",
+    },
+    activityModel: "vanLaar",
+    activityParameters: null,
+    plateSensors: [],
+    serial: {
+      dataBits: 8,
+      parity: "none",
+      stopBits: 1,
+      flowControl: "none",
+    },
+    transport: "rtuSerial",
+    network: {
+      host: "127.0.0.1",
+      port: 502,
+    },
+    probe: {
+      function: "holdingRegisters",
+      address: null,
+      count: 1,
+      unitId: null,
+      retries: 2,
+      timeoutMs: 1000,
+      backoffMs: 1000,
+    },
+    history: {
+      rawRetentionMinutes: 360,
+      bucketSeconds: 60,
+      bucketRetentionHours: 168,
+    },
+    acquisition: {
+      samplePeriodMs: 1000,
+      missedTicks: "skip",
+    },
+    simulation: {
+      ambientTemperature: 25,
+      heatUpMinutes: 20,
+      feedComposition: 0.3,
+      refluxMin: 1,
+      refluxMax: 5,
+      refluxChangeMinutes: 30,
+      noise: 0.1,
+      faultRate: 0.001,
+      timeScale: 1,
+      seed: null,
+    },
   });
   return (
     <SettingsContext.Provider
@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import { invokeTauri, logger } from "@/adapters/tauri";
+import { errorMessage, invokeTauri, isAppError, logger } from "@/adapters/tauri";
 import { toast } from "sonner";
 import { settingsSchema } from "@/schemas/settings";
 import { SettingsType } from "@/types";
@@ -24,26 +24,26 @@ export const useConnect = create<ConnectState>((set) => ({
 
     if (!result.success) {
       let errors = result;
-      toast.error("Missing settings");
+      toast.error(errors.error.issues[0]?.message ?? "Missing settings");
       logger.error(errors.error.message);
       return;
     }
     set({ loading: true });
 
-    toast.promise(invokeTauri("connect_modbus"), {
+    toast.promise(invokeTauri<string>("connect_modbus"), {
       loading: "Connecting...",
-      success: () => {
+      success: (message) => {
         set({ connected: true });
-        return "Connected";
+        return message;
       },
       error: (error) => {
         logger.error("Error connecting Modbus: ", error);
-        if (error === "Already connected") {
+        if (isAppError(error) && error.code === "alreadyConnected") {
           set({ connected: true });
           return "Already connected";
         }
         set({ connected: false });
-        return "Connection error";
+        return errorMessage(error, "Connection error");
       },
       finally: () => set({ loading: false }),
     });
@@ -1,18 +1,22 @@
 import { create } from "zustand";
 import { MAX_DATA_LENGTH } from "@/constants";
 import { invokeTauri } from "@/adapters/tauri";
-import { ColumnDataEntry } from "@/types";
+import { ColumnDataEntry, LinkState, PlaybackPositionType } from "@/types";
 
 type DataMode = "none" | "modbus" | "file" | "paused";
 
 interface DataState {
   columnData: ColumnDataEntry[];
   connected: DataMode;
+  linkState: LinkState;
+  playback: PlaybackPositionType | null;
   isLoading: boolean;
   filePath: string;
   fileProgress: number;
   setColumnData: (columnData: ColumnDataEntry) => void;
   setConnected: (connected: DataMode) => void;
+  setLinkState: (linkState: LinkState) => void;
+  setPlayback: (playback: PlaybackPositionType) => void;
   setLoading: (isLoading: boolean) => void;
   setFilePath: (filePath: string) => void;
   clearData: () => Promise<void>;
@@ -21,12 +25,19 @@ interface DataState {
 export const useData = create<DataState>((set) => ({
   columnData: [],
   connected: "none",
+  linkState: "disconnected",
+  playback: null,
   isLoading: false,
   filePath: "",
   fileProgress: 0,
   setColumnData: (columnData: ColumnDataEntry) => {
     set((state) => {
-      let newColumnData = [...state.columnData, columnData];
+      const last = state.columnData[state.columnData.length - 1];
+      // a playback looped or moved back starts the charts over
+      let newColumnData =
+        last && columnData.timestamp < last.timestamp
+          ? [columnData]
+          : [...state.columnData, columnData];
       if (newColumnData.length > MAX_DATA_LENGTH + 1) {
         newColumnData = [
           newColumnData[0],
@@ -37,18 +48,30 @@ export const useData = create<DataState>((set) => ({
         ...state,
         columnData: newColumnData,
         fileProgress: columnData.percentageComplete,
+        playback: state.playback && {
+          ...state.playback,
+          percentageComplete: columnData.percentageComplete,
+          index: Math.round(
+            (columnData.percentageComplete / 100) * state.playback.total,
+          ),
+          timestamp: columnData.timestamp,
+        },
       };
     }, true);
   },
   setConnected: (connected: DataMode) => set(() => ({ connected })),
+  setLinkState: (linkState: LinkState) => set(() => ({ linkState })),
+  setPlayback: (playback: PlaybackPositionType) =>
+    set(() => ({ playback, fileProgress: playback.percentageComplete })),
   setLoading: (isLoading: boolean) => set((state) => ({ ...state, isLoading })),
   setFilePath: (filePath: string) => set(() => ({ filePath })),
   setFileProgress: (fileProgress: number) => set(() => ({ fileProgress })),
   clearData: async () => {
-    await invokeTauri("cancel_column_data");
+    await invokeTauri("stop_acquisition");
     set(() => ({
       columnData: [],
       connected: "none",
+      playback: null,
       isLoading: false,
       filePath: "",
       fileProgress: 0,
@@ -1,10 +1,12 @@
 import { useContext } from "react";
 import { SettingsContext } from "@/contexts/settings-context";
-import { invokeTauri, logger } from "@/adapters/tauri";
+import { errorMessage, invokeTauri, logger } from "@/adapters/tauri";
 import { SettingsType } from "@/types";
+import { toast } from "sonner";
 
 export function useSettings() {
   const { settings, setSettings } = useContext(SettingsContext);
+  const MIN_PLATES = 2;
   const MAX_PLATES = 6;
 
   const addPlate = async () => {
@@ -17,7 +19,7 @@ export function useSettings() {
   };
 
   const removePlate = async () => {
-    let newNumberPlates = Math.max(settings.numberPlates - 1, 1);
+    let newNumberPlates = Math.max(settings.numberPlates - 1, MIN_PLATES);
     try {
       await saveSettings({ ...settings, numberPlates: newNumberPlates });
     } catch (error) {
@@ -31,6 +33,7 @@ export function useSettings() {
       setSettings(settings);
     } catch (error) {
       logger.error("Error fetching settings");
+      toast.error(errorMessage(error, "Error fetching settings"));
     }
   };
 
@@ -54,6 +57,7 @@ export function useSettings() {
     saveSettings,
     addPlate,
     removePlate,
+    MIN_PLATES,
     MAX_PLATES,
   };
 }
@@ -1,5 +1,11 @@
+import { errorMessage } from "@/adapters/tauri";
 import { Header } from "@/components/header";
 import { useData } from "@/hooks/useData";
+import {
+  AcquisitionStatus,
+  LinkHealth,
+  PlaybackPositionType,
+} from "@/types";
 import { ColumnDef } from "@tanstack/react-table";
 import { listen } from "@tauri-apps/api/event";
 import { useEffect } from "react";
@@ -10,26 +16,33 @@ import { TowerSection } from "./tower-section";
 
 type ColumnDataEntry = {
   timestamp: number;
+  pressure: number;
   temperatures: number[];
   compositions: number[];
+  vaporCompositions: number[];
   percentageComplete: number;
 };
 
 export type ColumnDataType = {
   timestamp: number;
+  pressure: number;
   temperatures: Array<number>;
   compositions: Array<number>;
+  vaporCompositions: Array<number>;
   percentageComplete: number;
 };
 
 export function DashboardPage() {
   const setColumnData = useData((state) => state.setColumnData);
   const setConnected = useData((state) => state.setConnected);
+  const setLinkState = useData((state) => state.setLinkState);
+  const setPlayback = useData((state) => state.setPlayback);
 
   useEffect(() => {
     const unlisten = listen<ColumnDataEntry>("column_data", (event) => {
       const handleListen = async () => {
-        if (event.payload.percentageComplete === 100) {
+        const looping = useData.getState().playback?.looping;
+        if (event.payload.percentageComplete === 100 && !looping) {
           setConnected("paused");
           toast.success("Playback complete!");
         }
@@ -43,6 +56,51 @@ export function DashboardPage() {
     };
   }, []);
 
+  useEffect(() => {
+    const unlisten = listen<LinkHealth>("connection_status", (event) => {
+      const { state, lastError } = event.payload;
+      const previous = useData.getState().linkState;
+      setLinkState(state);
+      if (state === "reconnecting" && previous !== "reconnecting") {
+        toast.warning(
+          `Connection lost, reconnecting... ${errorMessage(lastError, "")}`,
+        );
+      } else if (state === "connected" && previous === "reconnecting") {
+        toast.success("Connection restored");
+      }
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<PlaybackPositionType>(
+      "playback_position",
+      (event) => setPlayback(event.payload),
+    );
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<AcquisitionStatus>(
+      "acquisition_status",
+      (event) => {
+        const { state, lastError } = event.payload;
+        if (state !== "idle" || !lastError) return;
+        toast.error(
+          `Acquisition stopped: ${errorMessage(lastError, "unexpected error")}`,
+        );
+        setConnected("none");
+      },
+    );
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
   return (
     <div className="grid h-screen w-full grid-cols-6 grid-rows-11 gap-1 p-1">
       <Header className="col-span-6 row-span-1 rounded border" />
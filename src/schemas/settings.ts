@@ -1,25 +1,133 @@
 import { z } from "zod";
 
 export const formSchema = z.object({
+  transport: z.enum(["rtuSerial", "tcp", "rtuOverTcp", "simulated"]),
   usbPort: z.string(),
+  host: z.string(),
+  port: z.coerce.number().int().min(1).max(65535),
   baudrate: z
     .enum(["9600", "19200", "38400", "57600", "115200"])
     .transform(Number),
+  dataBits: z.enum(["7", "8"]).transform(Number),
+  parity: z.enum(["none", "odd", "even"]),
+  stopBits: z.enum(["1", "2"]).transform(Number),
+  flowControl: z.enum(["none", "software", "hardware"]),
   temperatureTop: z.coerce.number().min(0, "Too low").max(500, "Too high"),
   temperatureBottom: z.coerce.number().min(0, "Too low").max(500, "Too high"),
   unitId: z.coerce.number().int().min(0).max(255),
   timeout: z.coerce.number().min(100).max(10000),
+  samplePeriodMs: z.coerce.number().int().min(100).max(600000),
+  mixture: z.enum(["ethanolWater", "methanolWater", "acetoneWater"]),
+  pressure: z.coerce.number().gt(0, "Too low").max(5000, "Too high"),
+  activityModel: z.enum([
+    "vanLaar",
+    "margules2",
+    "margules3",
+    "wilson",
+    "nrtl",
+    "uniquac",
+  ]),
 });
 
-export const settingsSchema = z.object({
-  usbPort: z.string().min(1, "USB port is required"),
-  count: z.number().int().min(1, "Must be at least 1"),
-  timeout: z.number().min(100, "Too short").max(10000, "Too long"),
-  baudrate: z.number(),
-  unitId: z.number().int().min(0).max(255),
-  numberPlates: z.number().int().min(1, "At least 1 plate"),
-  temperatureAddress: z.object({
-    top: z.number().min(0, "Too low").max(500, "Too high"),
-    bottom: z.number().min(0, "Too low").max(500, "Too high"),
-  }),
-});
+// form field showing each settings field rejected by the backend
+export const FORM_FIELDS: Record<string, keyof z.infer<typeof formSchema>> = {
+  usbPort: "usbPort",
+  "network.host": "host",
+  "network.port": "port",
+  baudrate: "baudrate",
+  "serial.dataBits": "dataBits",
+  "serial.stopBits": "stopBits",
+  "temperatureAddress.top": "temperatureTop",
+  "temperatureAddress.bottom": "temperatureBottom",
+  timeout: "timeout",
+  "acquisition.samplePeriodMs": "samplePeriodMs",
+  "pressure.value": "pressure",
+};
+
+export const settingsSchema = z
+  .object({
+    version: z.number().int().min(0),
+    transport: z.enum(["rtuSerial", "tcp", "rtuOverTcp", "simulated"]),
+    usbPort: z.string(),
+    network: z.object({
+      host: z.string(),
+      port: z.number().int().min(1).max(65535),
+    }),
+    probe: z.object({
+      function: z.enum([
+        "coils",
+        "discreteInputs",
+        "holdingRegisters",
+        "inputRegisters",
+      ]),
+      address: z.number().int().min(0).max(65535).nullable(),
+      count: z.number().int().min(1).max(125),
+      unitId: z.number().int().min(0).max(255).nullable(),
+      retries: z.number().int().min(0).max(10),
+      timeoutMs: z.number().int().min(100, "Too short").max(10000, "Too long"),
+      backoffMs: z.number().int().min(0).max(60000),
+    }),
+    history: z.object({
+      rawRetentionMinutes: z.number().int().min(1),
+      bucketSeconds: z.number().int().min(1),
+      bucketRetentionHours: z.number().int().min(1),
+    }),
+    acquisition: z.object({
+      samplePeriodMs: z
+        .number()
+        .int()
+        .min(100, "Sample period too short")
+        .max(600000, "Sample period too long"),
+      missedTicks: z.enum(["skip", "burst", "delay"]),
+    }),
+    simulation: z.object({
+      ambientTemperature: z.number(),
+      heatUpMinutes: z.number().min(0),
+      feedComposition: z.number().min(0).max(1),
+      refluxMin: z.number().min(0),
+      refluxMax: z.number().min(0),
+      refluxChangeMinutes: z.number().min(0),
+      noise: z.number().min(0),
+      faultRate: z.number().min(0).max(1),
+      timeScale: z.number().positive(),
+      seed: z.number().int().min(0).nullable(),
+    }),
+    timeout: z.number().min(100, "Too short").max(10000, "Too long"),
+    baudrate: z.number().int().positive("Baudrate is required"),
+    serial: z.object({
+      dataBits: z.union([z.literal(7), z.literal(8)]),
+      parity: z.enum(["none", "odd", "even"]),
+      stopBits: z.union([z.literal(1), z.literal(2)]),
+      flowControl: z.enum(["none", "software", "hardware"]),
+    }),
+    unitId: z.number().int().min(0).max(255),
+    numberPlates: z.number().int().min(2, "At least 2 plates"),
+    mixture: z.enum(["ethanolWater", "methanolWater", "acetoneWater"]),
+    temperatureAddress: z.object({
+      top: z.number().min(0, "Too low").max(500, "Too high"),
+      bottom: z.number().min(0, "Too low").max(500, "Too high"),
+    }),
+    plateSensors: z.array(
+      z.object({
+        plate: z.number().int().min(0),
+        address: z.number().int().min(0).max(65535),
+        unitId: z.number().int().min(0).max(255).nullable(),
+        dataType: z.enum(["u16", "i16", "u32", "i32", "f32"]),
+        byteOrder: z.enum(["abcd", "cdab", "badc", "dcba"]),
+        scale: z.number(),
+        offset: z.number(),
+        unit: z.enum(["celsius", "fahrenheit", "kelvin"]),
+      }),
+    ),
+  })
+  .refine(
+    (settings) => settings.transport !== "rtuSerial" || settings.usbPort,
+    { message: "USB port is required", path: ["usbPort"] },
+  )
+  .refine(
+    (settings) =>
+      settings.transport === "rtuSerial" ||
+      settings.transport === "simulated" ||
+      settings.network.host,
+    { message: "Host is required", path: ["network", "host"] },
+  );
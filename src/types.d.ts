@@ -1,14 +1,130 @@
 export type SettingsType = {
+  version: number;
   usbPort: string;
   baudrate: number;
   temperatureAddress: {
     top: number;
     bottom: number;
   };
-  count: number;
   timeout: number;
   unitId: number;
   numberPlates: number;
+  mixture: MixtureId;
+  pressure: PressureSettingsType;
+  activityModel: ActivityModelKind;
+  activityParameters: Record<string, unknown> | null;
+  plateSensors: PlateSensorType[];
+  serial: SerialLineSettingsType;
+  transport: Transport;
+  network: NetworkSettingsType;
+  probe: ProbeSettingsType;
+  history: HistorySettingsType;
+  acquisition: AcquisitionSettingsType;
+  simulation: SimulationSettingsType;
+};
+
+export type MissedTicks = "skip" | "burst" | "delay";
+
+export type AcquisitionSettingsType = {
+  samplePeriodMs: number;
+  missedTicks: MissedTicks;
+};
+
+export type SimulationSettingsType = {
+  ambientTemperature: number;
+  heatUpMinutes: number;
+  feedComposition: number;
+  refluxMin: number;
+  refluxMax: number;
+  refluxChangeMinutes: number;
+  noise: number;
+  faultRate: number;
+  timeScale: number;
+  seed: number | null;
+};
+
+export type HistorySettingsType = {
+  rawRetentionMinutes: number;
+  bucketSeconds: number;
+  bucketRetentionHours: number;
+};
+
+export type ProbeFunction =
+  | "coils"
+  | "discreteInputs"
+  | "holdingRegisters"
+  | "inputRegisters";
+
+export type ProbeSettingsType = {
+  function: ProbeFunction;
+  address: number | null;
+  count: number;
+  unitId: number | null;
+  retries: number;
+  timeoutMs: number;
+  backoffMs: number;
+};
+
+export type Parity = "none" | "odd" | "even";
+
+export type FlowControl = "none" | "software" | "hardware";
+
+export type SerialLineSettingsType = {
+  dataBits: 7 | 8;
+  parity: Parity;
+  stopBits: 1 | 2;
+  flowControl: FlowControl;
+};
+
+export type Transport = "rtuSerial" | "tcp" | "rtuOverTcp" | "simulated";
+
+export type NetworkSettingsType = {
+  host: string;
+  port: number;
+};
+
+export type PlateSensorType = {
+  plate: number;
+  address: number;
+  unitId: number | null;
+  dataType: RegisterDataType;
+  byteOrder: ByteOrder;
+  scale: number;
+  offset: number;
+  unit: TemperatureUnit;
+};
+
+export type ActivityModelKind =
+  | "vanLaar"
+  | "margules2"
+  | "margules3"
+  | "wilson"
+  | "nrtl"
+  | "uniquac";
+
+export type RegisterDataType = "u16" | "i16" | "u32" | "i32" | "f32";
+
+export type ByteOrder = "abcd" | "cdab" | "badc" | "dcba";
+
+export type TemperatureUnit = "celsius" | "fahrenheit" | "kelvin";
+
+export type PressureUnit = "mmHg" | "kPa" | "bar" | "psi" | "atm";
+
+export type PressureSettingsType = {
+  value: number;
+  address: number | null;
+  dataType: RegisterDataType;
+  byteOrder: ByteOrder;
+  scale: number;
+  offset: number;
+  unit: PressureUnit;
+};
+
+export type MixtureId = "ethanolWater" | "methanolWater" | "acetoneWater";
+
+export type MixtureInfo = {
+  id: MixtureId;
+  name: string;
 };
 
 export type SettingsContextType = {
@@ -16,14 +132,168 @@ export type SettingsContextType = {
   setSettings: React.Dispatch<React.SetStateAction<SettingsType>>;
 };
 
+export type LinkState = "disconnected" | "connected" | "degraded" | "reconnecting";
+
+export type LinkHealth = {
+  state: LinkState;
+  consecutiveFailures: number;
+  lastSuccess: number | null;
+  lastError: AppError | null;
+  reconnectAttempts: number;
+};
+
+export type AcquisitionState = "idle" | "running" | "paused";
+
+export type AcquisitionStatus = {
+  state: AcquisitionState;
+  lastError: AppError | null;
+};
+
+export type PlaybackPacing = "samplePeriod" | "recorded";
+
+export type PlaybackPositionType = {
+  index: number;
+  total: number;
+  timestamp: number | null;
+  percentageComplete: number;
+  speed: number;
+  pacing: PlaybackPacing;
+  looping: boolean;
+  recompute: RecomputeType | null;
+};
+
+export type RecomputeType = {
+  pressure: number | null;
+};
+
+export type PlateDeviationType = {
+  plate: number;
+  mean: number;
+  max: number;
+};
+
+export type RecomputeReportType = {
+  entries: number;
+  outOfRange: number;
+  solverFailures: number;
+  plates: PlateDeviationType[];
+  applied: boolean;
+};
+
+export type AppErrorCode =
+  | "notConnected"
+  | "alreadyConnected"
+  | "connection"
+  | "timeout"
+  | "modbusException"
+  | "modbus"
+  | "settingsInvalid"
+  | "settingsRejected"
+  | "fileIo"
+  | "parse"
+  | "solver"
+  | "playbackFinished"
+  | "invalidState"
+  | "noData"
+  | "internal";
+
+export type AppError = {
+  code: AppErrorCode;
+  message?: string;
+  context?: string;
+  target?: string;
+  field?: string | null;
+  path?: string;
+  exception?: number;
+  plate?: number | null;
+  errors?: FieldErrorType[];
+};
+
+export type FieldErrorType = {
+  field: string;
+  message: string;
+};
+
 export type RegisterResponseType = {
   index: number;
   value: number;
 };
 
+export type CoilResponseType = {
+  index: number;
+  value: boolean;
+};
+
+export type WriteMultipleResponseType = {
+  start: number;
+  count: number;
+};
+
+export type RejectedRowType = {
+  row: number;
+  reason: string;
+};
+
+export type CsvOptionsType = {
+  delimiter: string;
+  decimalSeparator: string;
+};
+
+export type ImportReportType = {
+  sheet: string | null;
+  rowsImported: number;
+  rowsRejected: RejectedRowType[];
+  plateCount: number;
+};
+
+export type StatsType = {
+  min: number;
+  max: number;
+  mean: number;
+};
+
+export type HistoryBucketType = {
+  start: number;
+  end: number;
+  samples: number;
+  pressure: StatsType;
+  temperatures: StatsType[];
+  compositions: StatsType[];
+};
+
+export type HistoryWindowType = {
+  resolution: number;
+  points: HistoryBucketType[];
+};
+
+export type RunSummaryType = {
+  id: string;
+  startedAt: number;
+  endedAt: number | null;
+  samples: number;
+  plateCount: number;
+  mixture: string | null;
+  active: boolean;
+};
+
 type ColumnDataEntry = {
+  /** Milliseconds since the epoch */
   timestamp: number;
+  pressure: number;
   temperatures: number[];
   compositions: number[];
+  vaporCompositions: number[];
   percentageComplete: number;
 };
+
+type EquilibriumPoint = {
+  x: number;
+  y: number;
+  temperature: number;
+};
+
+type EquilibriumCurve = {
+  mixture: string;
+  pressure: number;
+  points: EquilibriumPoint[];
+};